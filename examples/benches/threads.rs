@@ -13,7 +13,7 @@ use std::{
     thread::{Thread, ThreadId},
 };
 
-use divan::{black_box, black_box_drop, AllocProfiler, Bencher};
+use divan::{black_box, black_box_drop, AllocProfiler, Bencher, ThreadRole};
 
 #[global_allocator]
 static ALLOC: AllocProfiler = AllocProfiler::system();
@@ -149,6 +149,20 @@ mod rw_lock {
             }
         }
     }
+
+    /// One thread writes while the rest read.
+    #[divan::bench]
+    fn read_write(bencher: Bencher) {
+        let lock = RwLock::new(0u64);
+
+        bencher.bench_roles(
+            &[ThreadRole::new("writer", 1), ThreadRole::rest("reader")],
+            |role| match role {
+                0 => *black_box(lock.write().unwrap()) += 1,
+                _ => black_box_drop(*lock.read().unwrap()),
+            },
+        );
+    }
 }
 
 /// Benchmark getting an integer or pointer uniquely identifying the current
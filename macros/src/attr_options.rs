@@ -300,6 +300,12 @@ impl AttrOptions {
                         &wrapped_value
                     }
 
+                    "pin" => {
+                        wrapped_value =
+                            quote! { #private_mod::IntoCpuPin::into_cpu_pin(#value) };
+                        &wrapped_value
+                    }
+
                     // If the option is a `Duration`, use `IntoDuration` to be
                     // polymorphic over `Duration` or `u64`/`f64` seconds.
                     "min_time" | "max_time" => {
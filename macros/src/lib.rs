@@ -115,9 +115,6 @@ pub fn bench(options: TokenStream, item: TokenStream) -> TokenStream {
         .map(|attr| attr.meta.path())
         .find(|path| path.is_ident("ignore"));
 
-    // If the function is `extern "ABI"`, it is wrapped in a Rust-ABI function.
-    let is_extern_abi = fn_sig.abi.is_some();
-
     let fn_args = &fn_sig.inputs;
 
     let type_param: Option<(usize, &syn::TypeParam)> = fn_sig
@@ -209,10 +206,23 @@ pub fn bench(options: TokenStream, item: TokenStream) -> TokenStream {
         })
         .unwrap_or_default();
 
+    // Fails the benchmark if the function returns `Err`, otherwise evaluating
+    // to the returned value.
+    //
+    // Calls are always wrapped in Rust-ABI closures, which also makes
+    // `extern "ABI"` functions usable as entries.
+    let check_result = |call: proc_macro2::TokenStream| {
+        quote! {{
+            #[allow(unused_imports)]
+            use #private_mod::ResultHelperFallback as _;
+            #private_mod::ResultHelper(#call).check()
+        }}
+    };
+
     // Creates a function expr for the benchmarking function, optionally
     // monomorphized with generic parameters.
     let make_bench_fn = |generics: &[&dyn ToTokens]| {
-        let mut fn_expr = if generics.is_empty() {
+        let fn_expr = if generics.is_empty() {
             // Use identifier as-is.
             fn_ident.to_token_stream()
         } else {
@@ -224,13 +234,10 @@ pub fn bench(options: TokenStream, item: TokenStream) -> TokenStream {
         match (fn_args.len(), &options.args_expr) {
             // Simple benchmark with no arguments provided.
             (0, None) => {
-                // Wrap in Rust ABI.
-                if is_extern_abi {
-                    fn_expr = quote! { || #fn_expr() };
-                }
+                let call = check_result(quote! { #fn_expr() });
 
                 quote! {
-                    #bench_entry_runner::Plain(|divan /* Bencher */| divan.bench(#fn_expr))
+                    #bench_entry_runner::Plain(|divan /* Bencher */| divan.bench(|| #call))
                 }
             }
 
@@ -240,40 +247,49 @@ pub fn bench(options: TokenStream, item: TokenStream) -> TokenStream {
 
             // `Bencher` function argument.
             (1, None) => {
-                // Wrap in Rust ABI.
-                if is_extern_abi {
-                    fn_expr = quote! { |divan /* Bencher */| #fn_expr(divan) };
-                }
+                let call = check_result(quote! { #fn_expr(divan) });
 
-                quote! { #bench_entry_runner::Plain(#fn_expr) }
+                quote! {
+                    #bench_entry_runner::Plain(|divan /* Bencher */| -> () { #call })
+                }
             }
 
             // Function argument comes from `args` option.
-            (1, Some(args)) => quote! {
-                #bench_entry_runner::Args(|| __DIVAN_ARGS.runner(
-                    || #arg_return_tokens { #args },
-
-                    |arg| #private_mod::ToStringHelper(arg).to_string(),
+            (1, Some(args)) => {
+                let call = check_result(quote! {
+                    #fn_expr(#private_mod::Arg::<#last_arg_type_tokens>::get(__divan_arg))
+                });
 
-                    |divan, __divan_arg| divan.bench(|| #fn_expr(
-                        #private_mod::Arg::<#last_arg_type_tokens>::get(__divan_arg)
-                    )),
-                ))
-            },
+                quote! {
+                    #bench_entry_runner::Args(|| __DIVAN_ARGS.runner(
+                        || #arg_return_tokens { #args },
 
-            // `Bencher` and `args` option function arguments.
-            (2, Some(args)) => quote! {
-                #bench_entry_runner::Args(|| __DIVAN_ARGS.runner(
-                    || #arg_return_tokens { #args },
+                        |arg| #private_mod::ToStringHelper(arg).to_string(),
 
-                    |arg| #private_mod::ToStringHelper(arg).to_string(),
+                        |divan, __divan_arg| divan.bench(|| #call),
+                    ))
+                }
+            }
 
-                    |divan, __divan_arg| #fn_expr(
+            // `Bencher` and `args` option function arguments.
+            (2, Some(args)) => {
+                let call = check_result(quote! {
+                    #fn_expr(
                         divan,
                         #private_mod::Arg::<#last_arg_type_tokens>::get(__divan_arg),
-                    ),
-                ))
-            },
+                    )
+                });
+
+                quote! {
+                    #bench_entry_runner::Args(|| __DIVAN_ARGS.runner(
+                        || #arg_return_tokens { #args },
+
+                        |arg| #private_mod::ToStringHelper(arg).to_string(),
+
+                        |divan, __divan_arg| -> () { #call },
+                    ))
+                }
+            }
 
             // Ensure `args` is set if arguments are provided after `Bencher`.
             (_, None) => quote! {
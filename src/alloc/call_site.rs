@@ -0,0 +1,220 @@
+//! Allocation call-site attribution.
+//!
+//! Backtraces are captured within the timed section, but symbols are resolved
+//! after each sample to reduce the impact on timing.
+
+use std::{
+    backtrace::Backtrace,
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    env,
+};
+
+/// The maximum number of non-internal frames kept per call site.
+const MAX_FRAMES: usize = 3;
+
+/// The maximum number of call sites reported per benchmark.
+pub(crate) const MAX_CALL_SITES: usize = 5;
+
+thread_local! {
+    /// Whether the current thread is within a timed section.
+    static ACTIVE: Cell<bool> = const { Cell::new(false) };
+
+    /// Whether the current thread is capturing a backtrace. Allocations made
+    /// while capturing are not tallied.
+    static CAPTURING: Cell<bool> = const { Cell::new(false) };
+
+    /// The number of allocations left until the next capture.
+    static COUNTDOWN: Cell<u32> = const { Cell::new(0) };
+
+    /// Unresolved backtraces with the number of allocations each represents.
+    static PENDING: RefCell<Vec<(Backtrace, u32)>> =
+        const { RefCell::new(Vec::new()) };
+}
+
+/// Enables or disables capturing on the current thread.
+#[inline]
+pub(crate) fn set_active(active: bool) {
+    _ = ACTIVE.try_with(|cell| cell.set(active));
+}
+
+/// Returns `true` if the current thread is capturing a backtrace.
+#[inline]
+pub(crate) fn is_capturing() -> bool {
+    CAPTURING.try_with(Cell::get).unwrap_or_default()
+}
+
+/// Captures a backtrace for every `interval` allocations within the timed
+/// section.
+#[inline]
+pub(crate) fn sample(interval: u32) {
+    if !ACTIVE.try_with(Cell::get).unwrap_or_default() {
+        return;
+    }
+
+    let should_capture = COUNTDOWN
+        .try_with(|countdown| match countdown.get() {
+            0 | 1 => {
+                countdown.set(interval);
+                true
+            }
+            n => {
+                countdown.set(n - 1);
+                false
+            }
+        })
+        .unwrap_or_default();
+
+    if should_capture {
+        capture(interval);
+    }
+}
+
+#[cold]
+#[inline(never)]
+fn capture(interval: u32) {
+    // Panic hooks may hold the backtrace lock while allocating.
+    if std::thread::panicking() {
+        return;
+    }
+
+    CAPTURING.set(true);
+
+    let backtrace = Backtrace::force_capture();
+    _ = PENDING.try_with(|pending| {
+        pending.borrow_mut().push((backtrace, interval));
+    });
+
+    CAPTURING.set(false);
+}
+
+/// Resolves backtraces captured on the current thread into call sites with
+/// their estimated number of allocations.
+pub(crate) fn take() -> Vec<(String, u64)> {
+    let Ok(pending) = PENDING.try_with(|pending| pending.take()) else {
+        return Vec::new();
+    };
+
+    if pending.is_empty() {
+        return Vec::new();
+    }
+
+    let current_dir = env::current_dir().ok();
+    let current_dir = current_dir.as_deref().and_then(|dir| dir.to_str());
+
+    let mut call_sites = HashMap::<String, u64>::new();
+    for (backtrace, count) in pending {
+        let call_site = short_trace(&backtrace.to_string(), current_dir);
+        *call_sites.entry(call_site).or_default() += count as u64;
+    }
+
+    call_sites.into_iter().collect()
+}
+
+/// Formats the innermost non-internal frames of a rendered backtrace, one per
+/// line.
+fn short_trace(backtrace: &str, current_dir: Option<&str>) -> String {
+    let mut frames = Vec::<String>::new();
+    let mut lines = backtrace.lines().peekable();
+
+    while let Some(line) = lines.next() {
+        // Frames are formatted as `N: name`, optionally followed by
+        // `at file:line:column`.
+        let Some((index, name)) = line.trim_start().split_once(": ") else {
+            continue;
+        };
+        if index.parse::<usize>().is_err() {
+            continue;
+        }
+
+        let location = lines
+            .next_if(|line| line.trim_start().starts_with("at "))
+            .map(|line| &line.trim_start()["at ".len()..]);
+
+        let name = strip_hash(name);
+        if is_internal(name) || name.contains("::__DIVAN_") {
+            continue;
+        }
+
+        frames.push(match location {
+            Some(location) => {
+                format!("{name} at {}", short_path(location, current_dir))
+            }
+            None => name.to_owned(),
+        });
+
+        if frames.len() == MAX_FRAMES {
+            break;
+        }
+    }
+
+    if frames.is_empty() {
+        "<unknown>".to_owned()
+    } else {
+        frames.join("\n")
+    }
+}
+
+/// Removes the trailing `::h<hash>` from a symbol name.
+fn strip_hash(name: &str) -> &str {
+    match name.rsplit_once("::h") {
+        Some((prefix, hash))
+            if hash.len() == 16
+                && hash.bytes().all(|b| b.is_ascii_hexdigit()) =>
+        {
+            prefix
+        }
+        _ => name,
+    }
+}
+
+/// Returns `true` for frames within the standard library, Divan, or runtime.
+fn is_internal(name: &str) -> bool {
+    let name = name.trim_start_matches(['<', '&']);
+    let name = name.strip_prefix("mut ").unwrap_or(name);
+    let name = name.strip_prefix("dyn ").unwrap_or(name);
+
+    ["std::", "core::", "alloc::", "divan::", "__", "_start"]
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+}
+
+fn short_path<'a>(path: &'a str, current_dir: Option<&str>) -> &'a str {
+    let path = current_dir
+        .and_then(|dir| path.strip_prefix(dir))
+        .map(|path| path.trim_start_matches(['/', '\\']))
+        .unwrap_or(path);
+
+    path.strip_prefix("./").unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short() {
+        let backtrace = "   0: divan::alloc::call_site::capture
+             at /src/divan/src/alloc/call_site.rs:80:21
+   1: __rustc::__rust_alloc
+   2: alloc::raw_vec::RawVec<T,A>::with_capacity_in
+             at /rustc/abc/library/alloc/src/raw_vec.rs:10:5
+   3: <my_crate::Foo as core::clone::Clone>::clone::h0123456789abcdef
+             at /work/my_crate/src/foo.rs:12:9
+   4: my_crate::bench::{{closure}}
+             at ./benches/bench.rs:5:20
+   5: core::ops::function::FnOnce::call_once
+   6: my_crate::__DIVAN_BENCH_BENCH::{{closure}}
+   7: my_crate::bench
+   8: my_crate::main";
+
+        assert_eq!(
+            short_trace(backtrace, Some("/work/my_crate")),
+            "<my_crate::Foo as core::clone::Clone>::clone at src/foo.rs:12:9\n\
+             my_crate::bench::{{closure}} at benches/bench.rs:5:20\n\
+             my_crate::bench"
+        );
+
+        assert_eq!(short_trace("   0: std::rt::lang_start", None), "<unknown>");
+    }
+}
@@ -0,0 +1,97 @@
+//! Allocation tallies shared by threads not spawned by Divan.
+//!
+//! Tallies are sharded by thread to reduce contention when many threads
+//! allocate at once.
+
+use std::{
+    cell::Cell,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+    alloc::{AllocOp, ThreadAllocCount, ThreadAllocTallyMap},
+    util::sync::{Atomic, AtomicFlag, CachePadded},
+};
+
+const SHARD_COUNT: usize = 16;
+
+type Shard = CachePadded<[[Atomic<ThreadAllocCount>; 2]; AllocOp::ALL.len()]>;
+
+#[allow(clippy::declare_interior_mutable_const)]
+const EMPTY_SHARD: Shard = {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const ZERO: Atomic<ThreadAllocCount> = Atomic::<ThreadAllocCount>::new(0);
+
+    #[allow(clippy::declare_interior_mutable_const)]
+    const EMPTY_TALLY: [Atomic<ThreadAllocCount>; 2] = [ZERO, ZERO];
+
+    CachePadded([EMPTY_TALLY; AllocOp::ALL.len()])
+};
+
+static SHARDS: [Shard; SHARD_COUNT] = [EMPTY_SHARD; SHARD_COUNT];
+
+/// Whether a sample is currently being timed.
+static ACTIVE: AtomicFlag = AtomicFlag::new(false);
+
+/// Assigns shards to threads in round-robin order.
+static NEXT_SHARD: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    /// Whether the current thread is recording a sample, in which case its
+    /// allocations are already tallied by its `ThreadAllocInfo`.
+    static IS_SAMPLING: Cell<bool> = const { Cell::new(false) };
+
+    static SHARD_INDEX: usize =
+        NEXT_SHARD.fetch_add(1, Ordering::Relaxed) % SHARD_COUNT;
+}
+
+/// Marks the current thread as recording a sample.
+#[inline]
+pub(crate) fn set_sampling(is_sampling: bool) {
+    _ = IS_SAMPLING.try_with(|cell| cell.set(is_sampling));
+}
+
+/// Clears previous tallies and starts tallying allocations from threads that
+/// are not recording samples.
+pub(crate) fn start() {
+    for shard in &SHARDS {
+        for tally in &shard.0 {
+            for value in tally {
+                value.store(0, Ordering::Relaxed);
+            }
+        }
+    }
+
+    ACTIVE.set(true);
+}
+
+/// Stops tallying and returns the tallies since `start`.
+pub(crate) fn stop() -> ThreadAllocTallyMap {
+    ACTIVE.set(false);
+
+    let mut tallies = ThreadAllocTallyMap::new();
+    for shard in &SHARDS {
+        for (tally, [count, size]) in tallies.values.iter_mut().zip(&shard.0) {
+            tally.count += count.load(Ordering::Relaxed);
+            tally.size += size.load(Ordering::Relaxed);
+        }
+    }
+    tallies
+}
+
+/// Tallies an allocation operation if a sample is being timed and the current
+/// thread is not recording it.
+#[inline]
+pub(crate) fn tally(op: AllocOp, size: usize) {
+    if !ACTIVE.get() || IS_SAMPLING.try_with(Cell::get).unwrap_or(true) {
+        return;
+    }
+
+    let Ok(shard_index) = SHARD_INDEX.try_with(|&index| index) else {
+        return;
+    };
+
+    let [count, total_size] = &SHARDS[shard_index].0[op as usize];
+    count.fetch_add(1, Ordering::Relaxed);
+    total_size.fetch_add(size as ThreadAllocCount, Ordering::Relaxed);
+}
@@ -0,0 +1,1280 @@
+use std::{alloc::*, cell::Cell, fmt, ptr::NonNull};
+
+use cfg_if::cfg_if;
+
+use crate::{stats::StatsSet, util::sync::AtomicFlag};
+
+#[cfg(target_os = "macos")]
+use crate::util::{sync::CachePadded, thread::local::PThreadKey};
+
+#[cfg(not(target_os = "macos"))]
+use std::cell::UnsafeCell;
+
+pub(crate) mod call_site;
+pub(crate) mod global;
+
+/// The `AllocProfiler` when running crate-internal tests.
+///
+/// This enables us to test it for:
+/// - Undefined behavior with Miri
+/// - Correctness when tallying
+#[cfg(test)]
+#[global_allocator]
+static ALLOC: AllocProfiler = AllocProfiler::system();
+
+/// Whether to ignore allocation info set during the benchmark.
+pub(crate) static IGNORE_ALLOC: AtomicFlag = AtomicFlag::new(false);
+
+thread_local! {
+    /// Whether `AllocProfiler` tallies allocations made by the current thread,
+    /// set on each benchmarking thread by the `alloc_profile` option.
+    static PROFILE_ALLOC: Cell<bool> = const { Cell::new(true) };
+}
+
+/// Sets whether `AllocProfiler` tallies allocations made by the current thread.
+#[inline]
+pub(crate) fn set_profiling(is_profiling: bool) {
+    _ = PROFILE_ALLOC.try_with(|cell| cell.set(is_profiling));
+}
+
+/// Returns `true` if `AllocProfiler` tallies allocations made by the current
+/// thread.
+#[inline]
+pub(crate) fn is_profiling() -> bool {
+    PROFILE_ALLOC.try_with(Cell::get).unwrap_or(true)
+}
+
+/// Measures [`GlobalAlloc`] memory usage.
+///
+/// # Examples
+///
+/// The default usage is to create a
+/// [`#[global_allocator]`](macro@global_allocator) that wraps the [`System`]
+/// allocator with [`AllocProfiler::system()`]:
+///
+/// ```
+/// use std::collections::*;
+/// use divan::AllocProfiler;
+///
+/// #[global_allocator]
+/// static ALLOC: AllocProfiler = AllocProfiler::system();
+///
+/// fn main() {
+///     divan::main();
+/// }
+///
+/// #[divan::bench(types = [
+///     Vec<i32>,
+///     LinkedList<i32>,
+///     HashSet<i32>,
+/// ])]
+/// fn from_iter<T>() -> T
+/// where
+///     T: FromIterator<i32>,
+/// {
+///     (0..100).collect()
+/// }
+///
+/// #[divan::bench(types = [
+///     Vec<i32>,
+///     LinkedList<i32>,
+///     HashSet<i32>,
+/// ])]
+/// fn drop<T>(bencher: divan::Bencher)
+/// where
+///     T: FromIterator<i32>,
+/// {
+///     bencher
+///         .with_inputs(|| (0..100).collect::<T>())
+///         .bench_values(std::mem::drop);
+/// }
+/// ```
+///
+/// Wrap other [`GlobalAlloc`] implementations like
+/// [`mimalloc`](https://docs.rs/mimalloc) with [`AllocProfiler::new()`]:
+///
+#[cfg_attr(not(miri), doc = "```")]
+#[cfg_attr(miri, doc = "```ignore")]
+/// use divan::AllocProfiler;
+/// use mimalloc::MiMalloc;
+///
+/// # #[cfg(not(miri))]
+/// #[global_allocator]
+/// static ALLOC: AllocProfiler<MiMalloc> = AllocProfiler::new(MiMalloc);
+/// ```
+///
+/// See [`string`](https://github.com/nvzqz/divan/blob/main/examples/benches/string.rs)
+/// and [`collections`](https://github.com/nvzqz/divan/blob/main/examples/benches/collections.rs)
+/// benchmarks for more examples.
+///
+/// # Implementation
+///
+/// Collecting allocation information happens at any point during which Divan is
+/// also measuring the time. As a result, counting allocations affects timing.
+///
+/// To reduce Divan's footprint during benchmarking:
+/// - Allocation information is recorded in thread-local storage to prevent
+///   contention when benchmarks involve multiple threads, either through
+///   options like [`threads`](macro@crate::bench#threads) or internally
+///   spawning their own threads.
+/// - It does not check for overflow and assumes it will not happen. This is
+///   subject to change in the future.
+/// - Fast thread-local storage access is assembly-optimized on macOS.
+///
+/// Allocation information is the only data Divan records outside of timing, and
+/// thus it also has the only code that affects timing. Steps for recording
+/// alloc info:
+/// 1. Load the thread-local slot for allocation information.
+///
+///    On macOS, this is via the
+///    [`gs`](https://github.com/nvzqz/divan/blob/v0.1.6/src/util/sync.rs#L34)/[`tpidrro_el0`](https://github.com/nvzqz/divan/blob/v0.1.6/src/util/sync.rs#L47)
+///    registers for
+///    [`pthread_getspecific`](https://pubs.opengroup.org/onlinepubs/9699919799/functions/pthread_getspecific.html).
+///    Although this is not guaranteed as stable ABI, in practice many programs
+///    assume these registers store thread-local data. [`thread_local!`] is used
+///    on all other platforms.
+///
+/// 2. Increment allocation operation invocation count and bytes count
+///    (a.k.a. size).
+///
+/// Allocation information is recorded in thread-local storage to prevent
+/// slowdowns from synchronized sharing when using multiple threads, through
+/// options like [`threads`](macro@crate::bench#threads).
+///
+/// By default, allocations in threads not controlled by Divan are not counted.
+/// See [all threads](#all-threads).
+///
+/// # Size Histogram
+///
+/// Totals alone cannot distinguish many small allocations from a few large
+/// ones. [`AllocProfiler::with_size_histogram()`] additionally counts the
+/// requested size of each allocation in power-of-two bins, which are shown
+/// under each benchmark:
+///
+/// ```
+/// use divan::AllocProfiler;
+///
+/// #[global_allocator]
+/// static ALLOC: AllocProfiler = AllocProfiler::system().with_size_histogram();
+/// ```
+///
+/// # Call Sites
+///
+/// [`AllocProfiler::with_call_sites()`] captures a backtrace for every `N`
+/// allocations within benchmarked code and shows the call sites that allocate
+/// the most under each benchmark:
+///
+/// ```
+/// use divan::AllocProfiler;
+///
+/// #[global_allocator]
+/// static ALLOC: AllocProfiler = AllocProfiler::system().with_call_sites(64);
+/// ```
+///
+/// Capturing backtraces is slow and happens inside the timed section, so timings
+/// are inflated while call sites are sampled, much more than by counting.
+/// Symbols are resolved outside of the timed section.
+///
+/// # All Threads
+///
+/// Benchmarked code may allocate in threads that Divan does not control, such
+/// as [`rayon`](https://docs.rs/rayon) or internal worker pools.
+/// [`AllocProfiler::with_all_threads()`] also counts allocations from those
+/// threads while a sample is being timed:
+///
+/// ```
+/// use divan::AllocProfiler;
+///
+/// #[global_allocator]
+/// static ALLOC: AllocProfiler = AllocProfiler::system().with_all_threads();
+/// ```
+///
+/// These are included in the `alloc`, `dealloc`, `grow`, and `shrink` tallies,
+/// but not in `max alloc`. They are tallied in shared atomic counters, which
+/// adds contention to allocations in other threads.
+///
+/// # Allocation Categories
+///
+/// Some allocations cost much more than others of the same size.
+/// [`AllocProfiler::with_alloc_categories()`] additionally counts these as
+/// extra rows under `alloc`:
+/// - `zeroed`: allocations through
+///   [`GlobalAlloc::alloc_zeroed`], such as [`vec![0; n]`](vec!).
+/// - `aligned`: allocations aligned to more than 16 bytes, which system
+///   allocators often handle on a slower path.
+/// - `huge`: allocations of at least the given number of bytes, which are
+///   likely to be served directly by `mmap` or `VirtualAlloc`. This is
+///   disabled by a threshold of 0.
+///
+/// Reallocations are categorized by their new size, so a [`Vec`] that grows
+/// past the huge threshold is counted as huge.
+///
+/// ```
+/// use divan::AllocProfiler;
+///
+/// #[global_allocator]
+/// static ALLOC: AllocProfiler =
+///     AllocProfiler::system().with_alloc_categories(128 * 1024);
+/// ```
+///
+/// These are subsets of `alloc`, so each allocation may be counted in several
+/// categories. Reallocations are not categorized.
+#[derive(Debug, Default)]
+pub struct AllocProfiler<Alloc = System> {
+    alloc: Alloc,
+    config: ProfilerConfig,
+}
+
+/// Optional `AllocProfiler` modes.
+#[derive(Clone, Copy, Debug, Default)]
+struct ProfilerConfig {
+    size_histogram: bool,
+
+    /// Captures call sites every this many allocations, or never if 0.
+    call_site_interval: u32,
+
+    all_threads: bool,
+
+    alloc_categories: bool,
+
+    /// The minimum size of allocations counted as `AllocOp::Huge`.
+    huge_threshold: usize,
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for AllocProfiler<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if self.is_bypassed() {
+            return self.alloc.alloc(layout);
+        }
+
+        // Tally allocation count.
+        if let Some(mut info) = ThreadAllocInfo::try_current() {
+            // SAFETY: We have exclusive access.
+            let info = unsafe { info.as_mut() };
+
+            info.tally_alloc(layout.size());
+
+            if self.config.size_histogram {
+                info.size_bins.tally(layout.size());
+            }
+
+            self.tally_categories(info, layout, false);
+        };
+
+        self.tally_global(AllocOp::Alloc, layout.size());
+        self.tally_global_categories(layout, false);
+        self.sample_call_site();
+
+        self.alloc.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        if self.is_bypassed() {
+            return self.alloc.alloc_zeroed(layout);
+        }
+
+        // Tally allocation count.
+        if let Some(mut info) = ThreadAllocInfo::try_current() {
+            // SAFETY: We have exclusive access.
+            let info = unsafe { info.as_mut() };
+
+            info.tally_alloc(layout.size());
+
+            if self.config.size_histogram {
+                info.size_bins.tally(layout.size());
+            }
+
+            self.tally_categories(info, layout, true);
+        };
+
+        self.tally_global(AllocOp::Alloc, layout.size());
+        self.tally_global_categories(layout, true);
+        self.sample_call_site();
+
+        self.alloc.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(
+        &self,
+        ptr: *mut u8,
+        layout: Layout,
+        new_size: usize,
+    ) -> *mut u8 {
+        if self.is_bypassed() {
+            return self.alloc.realloc(ptr, layout, new_size);
+        }
+
+        // Reallocations are categorized by their new size.
+        //
+        // SAFETY: The caller guarantees that `new_size` is valid for the
+        // alignment of `layout`.
+        let new_layout = unsafe {
+            Layout::from_size_align_unchecked(new_size, layout.align())
+        };
+
+        // Tally reallocation count.
+        if let Some(mut info) = ThreadAllocInfo::try_current() {
+            // SAFETY: We have exclusive access.
+            let info = unsafe { info.as_mut() };
+
+            info.tally_realloc(layout.size(), new_size);
+
+            if self.config.size_histogram {
+                info.size_bins.tally(new_size);
+            }
+
+            self.tally_categories(info, new_layout, false);
+        };
+
+        if self.config.all_threads {
+            let (diff, is_shrink) = new_size.overflowing_sub(layout.size());
+            let abs_diff = (diff as isize).wrapping_abs() as usize;
+
+            global::tally(AllocOp::realloc(is_shrink), abs_diff);
+        }
+
+        self.tally_global_categories(new_layout, false);
+
+        self.sample_call_site();
+
+        self.alloc.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if self.is_bypassed() {
+            return self.alloc.dealloc(ptr, layout);
+        }
+
+        // Tally deallocation count.
+        if let Some(mut info) = ThreadAllocInfo::try_current() {
+            // SAFETY: We have exclusive access.
+            let info = unsafe { info.as_mut() };
+
+            info.tally_dealloc(layout.size());
+        };
+
+        self.tally_global(AllocOp::Dealloc, layout.size());
+
+        self.alloc.dealloc(ptr, layout)
+    }
+}
+
+impl AllocProfiler {
+    /// Profiles the [`System`] allocator.
+    #[inline]
+    pub const fn system() -> Self {
+        Self::new(System)
+    }
+}
+
+impl<A> AllocProfiler<A> {
+    /// Profiles a [`GlobalAlloc`].
+    #[inline]
+    pub const fn new(alloc: A) -> Self {
+        Self { alloc, config: ProfilerConfig::DEFAULT }
+    }
+
+    /// Also counts allocations by size in power-of-two bins.
+    ///
+    /// Each `alloc`, `grow`, and `shrink` is counted in the smallest bin that
+    /// fits its requested size. This adds a small amount of overhead to every
+    /// allocation.
+    #[inline]
+    pub const fn with_size_histogram(self) -> Self {
+        let mut config = self.config;
+        config.size_histogram = true;
+        self.with_config(config)
+    }
+
+    /// Also attributes allocations to call sites by capturing a backtrace
+    /// every `interval` allocations within benchmarked code.
+    ///
+    /// The most frequent call sites are shown under each benchmark, with the
+    /// number of allocations per iteration estimated from sampling. Use an
+    /// `interval` of 1 to capture every allocation.
+    ///
+    /// Backtraces are captured inside the timed section, so timings are
+    /// inflated while call sites are sampled, increasingly so for smaller
+    /// intervals. Compare timings against a run without call sites, or use
+    /// [`alloc_profile = false`](macro@crate::bench#alloc_profile) for
+    /// benchmarks whose timings matter.
+    #[inline]
+    pub const fn with_call_sites(self, interval: u32) -> Self {
+        let mut config = self.config;
+        config.call_site_interval = if interval == 0 { 1 } else { interval };
+        self.with_config(config)
+    }
+
+    /// Also counts allocations from threads not spawned by Divan while a
+    /// sample is being timed.
+    ///
+    /// See [all threads](#all-threads) for details.
+    #[inline]
+    pub const fn with_all_threads(self) -> Self {
+        let mut config = self.config;
+        config.all_threads = true;
+        self.with_config(config)
+    }
+
+    /// Also counts zeroed, over-aligned, and huge allocations as separate
+    /// categories. These count calls that are also counted by `alloc`, `grow`,
+    /// or `shrink`.
+    ///
+    /// Allocations of at least `huge_threshold` bytes are counted as huge, and
+    /// a `huge_threshold` of 0 disables counting huge allocations.
+    ///
+    /// See [allocation categories](#allocation-categories) for details.
+    #[inline]
+    pub const fn with_alloc_categories(self, huge_threshold: usize) -> Self {
+        let mut config = self.config;
+        config.alloc_categories = true;
+        config.huge_threshold = huge_threshold;
+        self.with_config(config)
+    }
+
+    #[inline]
+    const fn with_config(self, config: ProfilerConfig) -> Self {
+        // Moving `alloc` out of `self` is not yet allowed in `const fn` for
+        // generic types that may implement `Drop`.
+        //
+        // SAFETY: `self` is forgotten, so `alloc` is not dropped twice.
+        let alloc = unsafe { std::ptr::read(&self.alloc) };
+        std::mem::forget(self);
+
+        Self { alloc, config }
+    }
+
+    /// Returns `true` if this allocation should not be tallied, because
+    /// profiling is disabled for the current benchmark or a call site is being
+    /// captured.
+    #[inline]
+    fn is_bypassed(&self) -> bool {
+        !is_profiling()
+            || (self.config.call_site_interval != 0
+                && call_site::is_capturing())
+    }
+
+    #[inline]
+    fn sample_call_site(&self) {
+        if self.config.call_site_interval != 0 {
+            call_site::sample(self.config.call_site_interval);
+        }
+    }
+
+    #[inline]
+    fn tally_global(&self, op: AllocOp, size: usize) {
+        if self.config.all_threads {
+            global::tally(op, size);
+        }
+    }
+
+    #[inline]
+    fn tally_categories(
+        &self,
+        info: &mut ThreadAllocInfo,
+        layout: Layout,
+        is_zeroed: bool,
+    ) {
+        if self.config.alloc_categories {
+            for op in self.config.categories_of(layout, is_zeroed) {
+                info.tally_op(op, layout.size());
+            }
+        }
+    }
+
+    #[inline]
+    fn tally_global_categories(&self, layout: Layout, is_zeroed: bool) {
+        if self.config.alloc_categories && self.config.all_threads {
+            for op in self.config.categories_of(layout, is_zeroed) {
+                global::tally(op, layout.size());
+            }
+        }
+    }
+}
+
+impl ProfilerConfig {
+    const DEFAULT: Self = Self {
+        size_histogram: false,
+        call_site_interval: 0,
+        all_threads: false,
+        alloc_categories: false,
+        huge_threshold: 0,
+    };
+
+    /// Alignments above this are counted as `AllocOp::Aligned`.
+    ///
+    /// This is the largest alignment that common system allocators provide
+    /// without extra work.
+    const MAX_NATURAL_ALIGN: usize = 16;
+
+    /// Returns the extra categories that an allocation belongs to.
+    #[inline]
+    fn categories_of(
+        &self,
+        layout: Layout,
+        is_zeroed: bool,
+    ) -> impl Iterator<Item = AllocOp> {
+        [
+            is_zeroed.then_some(AllocOp::Zeroed),
+            (layout.align() > Self::MAX_NATURAL_ALIGN)
+                .then_some(AllocOp::Aligned),
+            (self.huge_threshold != 0 && layout.size() >= self.huge_threshold)
+                .then_some(AllocOp::Huge),
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+/// Thread-local allocation information.
+#[derive(Clone, Default)]
+#[repr(C)]
+pub(crate) struct ThreadAllocInfo {
+    // NOTE: `tallies` should be ordered first so that `tally_realloc` can
+    // directly index `&self` without an offset.
+    pub tallies: ThreadAllocTallyMap,
+
+    // NOTE: Max size and count are signed for convenience but can never be
+    // negative due to it being initialized to 0.
+    //
+    // PERF: Grouping current/max fields together by count and size makes
+    // `tally_alloc` take the least time on M1 Mac.
+    pub current_count: ThreadAllocCountSigned,
+    pub max_count: ThreadAllocCountSigned,
+    pub current_size: ThreadAllocCountSigned,
+    pub max_size: ThreadAllocCountSigned,
+
+    /// Allocation counts by size, if enabled by
+    /// [`AllocProfiler::with_size_histogram`].
+    pub size_bins: AllocSizeBins,
+}
+
+#[cfg(not(target_os = "macos"))]
+thread_local! {
+    /// Instance specific to the current thread.
+    ///
+    /// On macOS, we use `ALLOC_PTHREAD_KEY` instead.
+    static CURRENT_THREAD_INFO: UnsafeCell<ThreadAllocInfo> = const {
+        UnsafeCell::new(ThreadAllocInfo::new())
+    };
+}
+
+#[cfg(target_os = "macos")]
+static ALLOC_PTHREAD_KEY: CachePadded<PThreadKey<ThreadAllocInfo>> =
+    CachePadded(PThreadKey::new());
+
+impl ThreadAllocInfo {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            tallies: ThreadAllocTallyMap::new(),
+            max_count: 0,
+            current_count: 0,
+            max_size: 0,
+            current_size: 0,
+            size_bins: AllocSizeBins::new(),
+        }
+    }
+
+    /// Returns the current thread's allocation information, initializing it on
+    /// first access.
+    ///
+    /// Returns `None` if the thread is terminating and has thus deallocated its
+    /// local instance.
+    #[inline]
+    pub fn current() -> Option<NonNull<Self>> {
+        cfg_if! {
+            if #[cfg(target_os = "macos")] {
+                return Self::try_current().or_else(slow_impl);
+            } else {
+                Self::try_current()
+            }
+        }
+
+        #[cfg(target_os = "macos")]
+        #[cold]
+        #[inline(never)]
+        fn slow_impl() -> Option<NonNull<ThreadAllocInfo>> {
+            unsafe {
+                let layout = Layout::new::<ThreadAllocInfo>();
+
+                let Some(info_alloc) =
+                    NonNull::new(unsafe { System.alloc_zeroed(layout) })
+                else {
+                    handle_alloc_error(layout);
+                };
+
+                let success = ALLOC_PTHREAD_KEY.0.set(
+                    info_alloc.as_ptr().cast(),
+                    |this| {
+                        System.dealloc(
+                            this.as_ptr().cast(),
+                            Layout::new::<ThreadAllocInfo>(),
+                        );
+                    },
+                );
+
+                if !success {
+                    System.dealloc(info_alloc.as_ptr(), layout);
+                    return None;
+                }
+
+                // When using static thread local key, write directly because it
+                // is undefined behavior to call `pthread_setspecific` with a
+                // key that didn't originate from `pthread_key_create`.
+                #[cfg(all(
+                    not(miri),
+                    not(feature = "dyn_thread_local"),
+                    target_arch = "x86_64"
+                ))]
+                unsafe {
+                    crate::util::thread::local::fast::set_static_thread_local(
+                        info_alloc.as_ptr(),
+                    );
+                };
+
+                Some(info_alloc.cast())
+            }
+        }
+    }
+
+    /// Returns the current thread's allocation information if initialized.
+    ///
+    /// Returns `None` if the instance has not yet been allocated or the thread
+    /// is terminating and has thus deallocated its local instance.
+    #[inline]
+    pub fn try_current() -> Option<NonNull<Self>> {
+        cfg_if! {
+            if #[cfg(target_os = "macos")] {
+                // Fast path: static thread local.
+                #[cfg(all(
+                    not(miri),
+                    not(feature = "dyn_thread_local"),
+                    target_arch = "x86_64",
+                ))]
+                return NonNull::new(unsafe {
+                    crate::util::thread::local::fast::get_static_thread_local::<Self>().cast_mut()
+                });
+
+                #[allow(unreachable_code)]
+                ALLOC_PTHREAD_KEY.0.get()
+            } else {
+                CURRENT_THREAD_INFO.try_with(|info| unsafe {
+                    NonNull::new_unchecked(info.get())
+                }).ok()
+            }
+        }
+    }
+
+    /// Sets 0 to all values.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Returns a failure message if the bytes retained by a sample of
+    /// `sample_size` iterations exceed `max_bytes` per iteration.
+    ///
+    /// This assumes `current_count` and `current_size` are relative to the
+    /// start of the sample.
+    pub fn check_max_retained_bytes(
+        &self,
+        max_bytes: u64,
+        sample_size: u32,
+    ) -> Result<(), String> {
+        let retained = self.current_size as i128;
+        if retained <= max_bytes as i128 * sample_size as i128 {
+            return Ok(());
+        }
+
+        let count = self.current_count;
+        let allocs = if count == 1 { "allocation" } else { "allocations" };
+        let iters = if sample_size == 1 { "iteration" } else { "iterations" };
+
+        Err(format!(
+            "benchmark exceeded `max_retained_bytes = {max_bytes}` per iteration by retaining {retained} bytes in {count} {allocs} over {sample_size} {iters}",
+        ))
+    }
+
+    /// Returns a failure message if a sample did not free all of the memory it
+    /// allocated.
+    ///
+    /// This assumes `current_count` and `current_size` are relative to the
+    /// start of the sample.
+    pub fn check_leaks(&self) -> Result<(), String> {
+        if self.current_count <= 0 && self.current_size <= 0 {
+            return Ok(());
+        }
+
+        let count = self.current_count;
+        let allocs = if count == 1 { "allocation" } else { "allocations" };
+
+        Err(format!(
+            "benchmark leaked {} bytes in {count} {allocs}",
+            self.current_size,
+        ))
+    }
+
+    /// Tallies the total count and size of the allocation operation.
+    #[inline]
+    pub fn tally_alloc(&mut self, size: usize) {
+        self.tally_op(AllocOp::Alloc, size);
+
+        self.current_count += 1;
+        self.max_count = self.max_count.max(self.current_count);
+
+        self.current_size += size as ThreadAllocCountSigned;
+        self.max_size = self.max_size.max(self.current_size);
+    }
+
+    /// Tallies the total count and size of the deallocation operation.
+    #[inline]
+    pub fn tally_dealloc(&mut self, size: usize) {
+        self.tally_op(AllocOp::Dealloc, size);
+
+        self.current_count -= 1;
+        self.current_size -= size as ThreadAllocCountSigned;
+    }
+
+    /// Tallies the total count and size of the reallocation operation.
+    #[inline]
+    pub fn tally_realloc(&mut self, old_size: usize, new_size: usize) {
+        let (diff, is_shrink) = new_size.overflowing_sub(old_size);
+        let diff = diff as isize;
+        let abs_diff = diff.wrapping_abs() as usize;
+
+        self.tally_op(AllocOp::realloc(is_shrink), abs_diff);
+
+        // NOTE: Realloc does not change allocation count.
+        self.current_size += diff as ThreadAllocCountSigned;
+        self.max_size = self.max_size.max(self.current_size);
+    }
+
+    /// Tallies the total count and size of the allocation operation.
+    #[inline]
+    fn tally_op(&mut self, op: AllocOp, size: usize) {
+        let tally = self.tallies.get_mut(op);
+        tally.count += 1;
+        tally.size += size as ThreadAllocCount;
+    }
+}
+
+/// Allocation operation count and size.
+///
+/// # Memory Layout
+///
+/// Aligning to 16 nudges the compiler to emit aligned SIMD operations.
+///
+/// Placing `count` first generates less code on AArch64.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(C, align(16))]
+pub struct AllocTally<Count> {
+    /// The number of times this operation was performed.
+    pub count: Count,
+
+    /// The amount of memory this operation changed.
+    pub size: Count,
+}
+
+pub(crate) type ThreadAllocCount = condtype::num::Usize64;
+pub(crate) type ThreadAllocCountSigned = condtype::num::Isize64;
+
+pub(crate) type ThreadAllocTally = AllocTally<ThreadAllocCount>;
+
+pub(crate) type TotalAllocTally = AllocTally<u128>;
+
+impl AllocTally<StatsSet<f64>> {
+    pub(crate) fn is_zero(&self) -> bool {
+        self.count.is_zero() && self.size.is_zero()
+    }
+}
+
+impl<C> AllocTally<C> {
+    #[inline]
+    pub(crate) fn as_array(&self) -> &[C; 2] {
+        // SAFETY: This is `#[repr(C)]`, so we can treat it as a contiguous
+        // sequence of items.
+        unsafe { &*(self as *const _ as *const _) }
+    }
+}
+
+/// The number of [`AllocSizeBins`], enough to fit any valid allocation size
+/// because sizes cannot exceed [`isize::MAX`].
+pub(crate) const ALLOC_SIZE_BIN_COUNT: usize = usize::BITS as usize;
+
+/// Allocation counts by power-of-two size, where bin `i` holds sizes in
+/// `(2^(i-1), 2^i]`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct AllocSizeBins<T = ThreadAllocCount> {
+    pub values: [T; ALLOC_SIZE_BIN_COUNT],
+}
+
+impl<T: Default + Copy> Default for AllocSizeBins<T> {
+    #[inline]
+    fn default() -> Self {
+        Self { values: [T::default(); ALLOC_SIZE_BIN_COUNT] }
+    }
+}
+
+impl AllocSizeBins {
+    #[inline]
+    pub const fn new() -> Self {
+        Self { values: [0; ALLOC_SIZE_BIN_COUNT] }
+    }
+
+    /// Returns the index of the smallest bin that fits `size`.
+    #[inline]
+    pub fn index_of(size: usize) -> usize {
+        (usize::BITS - size.saturating_sub(1).leading_zeros()) as usize
+    }
+
+    #[inline]
+    pub fn tally(&mut self, size: usize) {
+        // Allocation sizes cannot exceed `isize::MAX`, so this is always in
+        // bounds.
+        if let Some(count) = self.values.get_mut(Self::index_of(size)) {
+            *count += 1;
+        }
+    }
+}
+
+impl AllocSizeBins<f64> {
+    /// Returns `(max_size, count)` pairs for bins with allocations.
+    pub fn non_empty(&self) -> impl Iterator<Item = (u64, f64)> + '_ {
+        self.values
+            .iter()
+            .enumerate()
+            .filter(|(_, &count)| count > 0.0)
+            .map(|(index, &count)| (1 << index, count))
+    }
+}
+
+/// Allocation number categories.
+///
+/// Note that grow/shrink are first to improve code generation for `realloc`.
+///
+/// `Zeroed`, `Aligned`, and `Huge` are subsets of `Alloc` that are only
+/// tallied with [`AllocProfiler::with_alloc_categories`].
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AllocOp {
+    Grow,
+    Shrink,
+    Alloc,
+    Dealloc,
+    Zeroed,
+    Aligned,
+    Huge,
+}
+
+impl AllocOp {
+    pub const ALL: [Self; 7] = {
+        use AllocOp::*;
+
+        // Use same order as declared so that it can be indexed as-is.
+        [Grow, Shrink, Alloc, Dealloc, Zeroed, Aligned, Huge]
+    };
+
+    /// The order in which tallies are displayed.
+    pub const DISPLAY: [Self; 7] = {
+        use AllocOp::*;
+
+        [Alloc, Dealloc, Grow, Shrink, Zeroed, Aligned, Huge]
+    };
+
+    #[inline]
+    pub fn realloc(shrink: bool) -> Self {
+        // This generates the same code as `std::mem::transmute`.
+        if shrink {
+            Self::Shrink
+        } else {
+            Self::Grow
+        }
+    }
+
+    #[inline]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Grow => "grow",
+            Self::Shrink => "shrink",
+            Self::Alloc => "alloc",
+            Self::Dealloc => "dealloc",
+            Self::Zeroed => "zeroed",
+            Self::Aligned => "aligned",
+            Self::Huge => "huge",
+        }
+    }
+
+    #[inline]
+    pub fn prefix(self) -> &'static str {
+        match self {
+            Self::Grow => "grow:",
+            Self::Shrink => "shrink:",
+            Self::Alloc => "alloc:",
+            Self::Dealloc => "dealloc:",
+            Self::Zeroed => "zeroed:",
+            Self::Aligned => "aligned:",
+            Self::Huge => "huge:",
+        }
+    }
+}
+
+/// Values keyed by `AllocOp`.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct AllocOpMap<T> {
+    pub values: [T; AllocOp::ALL.len()],
+}
+
+pub(crate) type ThreadAllocTallyMap = AllocOpMap<ThreadAllocTally>;
+
+pub(crate) type TotalAllocTallyMap = AllocOpMap<TotalAllocTally>;
+
+impl<T: fmt::Debug> fmt::Debug for AllocOpMap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map()
+            .entries(AllocOp::ALL.iter().map(|&op| (op.name(), self.get(op))))
+            .finish()
+    }
+}
+
+impl ThreadAllocTallyMap {
+    #[inline]
+    pub const fn new() -> Self {
+        unsafe { std::mem::transmute([0u8; size_of::<Self>()]) }
+    }
+
+    /// Returns the number of operations that allocated memory, which excludes
+    /// `dealloc`.
+    #[inline]
+    pub fn alloc_count(&self) -> ThreadAllocCount {
+        [AllocOp::Alloc, AllocOp::Grow, AllocOp::Shrink]
+            .map(|op| self.get(op).count)
+            .into_iter()
+            .sum()
+    }
+
+    /// Returns a failure message if the number of allocations in a sample of
+    /// `sample_size` iterations exceeds `max_allocs` per iteration.
+    pub fn check_max_allocs(
+        &self,
+        max_allocs: u64,
+        sample_size: u32,
+    ) -> Result<(), String> {
+        let alloc_count = self.alloc_count() as u128;
+        if alloc_count <= max_allocs as u128 * sample_size as u128 {
+            return Ok(());
+        }
+
+        let iters = if sample_size == 1 { "iteration" } else { "iterations" };
+
+        let mut message = format!(
+            "benchmark exceeded `max_allocs = {max_allocs}` per iteration with {alloc_count} allocations over {sample_size} {iters}:"
+        );
+
+        for op in AllocOp::DISPLAY {
+            let tally = self.get(op);
+            if tally.count != 0 {
+                message += &format!(
+                    "\n  {:<8} {} ({} bytes)",
+                    op.prefix(),
+                    tally.count,
+                    tally.size
+                );
+            }
+        }
+
+        Err(message)
+    }
+
+    /// Returns `true` if all tallies are 0.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.values.iter().all(|tally| tally.count == 0 && tally.size == 0)
+    }
+
+    pub fn add(&mut self, other: &Self) {
+        for (value, other) in self.values.iter_mut().zip(&other.values) {
+            value.count += other.count;
+            value.size += other.size;
+        }
+    }
+
+    pub fn add_to_total(&self, total: &mut TotalAllocTallyMap) {
+        for (i, value) in self.values.iter().enumerate() {
+            total.values[i].count += value.count as u128;
+            total.values[i].size += value.size as u128;
+        }
+    }
+}
+
+impl<T> AllocOpMap<T> {
+    #[cfg(test)]
+    pub fn from_fn<F>(f: F) -> Self
+    where
+        F: FnMut(AllocOp) -> T,
+    {
+        Self { values: AllocOp::ALL.map(f) }
+    }
+
+    #[inline]
+    pub const fn get(&self, op: AllocOp) -> &T {
+        &self.values[op as usize]
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, op: AllocOp) -> &mut T {
+        &mut self.values[op as usize]
+    }
+}
+
+#[cfg(feature = "internal_benches")]
+mod benches {
+    use super::*;
+
+    // We want the approach to scale well with thread count.
+    const THREADS: &[usize] = &[0, 1, 2, 4, 16];
+
+    #[crate::bench(crate = crate, threads = THREADS)]
+    fn tally_alloc(bencher: crate::Bencher) {
+        IGNORE_ALLOC.set(true);
+
+        // Using 0 simulates tallying without affecting benchmark reporting.
+        let size = crate::black_box(0);
+
+        bencher.bench(|| {
+            if let Some(mut info) = ThreadAllocInfo::try_current() {
+                // SAFETY: We have exclusive access.
+                let info = unsafe { info.as_mut() };
+
+                info.tally_alloc(size);
+            }
+        })
+    }
+
+    #[crate::bench(crate = crate, threads = THREADS)]
+    fn tally_dealloc(bencher: crate::Bencher) {
+        IGNORE_ALLOC.set(true);
+
+        // Using 0 simulates tallying without affecting benchmark reporting.
+        let size = crate::black_box(0);
+
+        bencher.bench(|| {
+            if let Some(mut info) = ThreadAllocInfo::try_current() {
+                // SAFETY: We have exclusive access.
+                let info = unsafe { info.as_mut() };
+
+                info.tally_dealloc(size);
+            }
+        })
+    }
+
+    #[crate::bench(crate = crate, threads = THREADS)]
+    fn tally_realloc(bencher: crate::Bencher) {
+        IGNORE_ALLOC.set(true);
+
+        // Using 0 simulates tallying without affecting benchmark reporting.
+        let new_size = crate::black_box(0);
+        let old_size = crate::black_box(0);
+
+        bencher.bench(|| {
+            if let Some(mut info) = ThreadAllocInfo::try_current() {
+                // SAFETY: We have exclusive access.
+                let info = unsafe { info.as_mut() };
+
+                info.tally_realloc(old_size, new_size);
+            }
+        })
+    }
+
+    #[crate::bench_group(crate = crate, threads = THREADS)]
+    mod current {
+        use super::*;
+
+        #[crate::bench(crate = crate)]
+        fn init() -> Option<NonNull<ThreadAllocInfo>> {
+            ThreadAllocInfo::current()
+        }
+
+        #[crate::bench(crate = crate)]
+        fn r#try() -> Option<NonNull<ThreadAllocInfo>> {
+            ThreadAllocInfo::try_current()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tests that `AllocProfiler` is counting correctly.
+    #[test]
+    fn tally() {
+        // Initialize the thread's alloc info.
+        //
+        // SAFETY: This cannot be kept as a reference and is instead a raw
+        // pointer because a reference would cause undefined behavior when
+        // `AllocProfiler` attempts to update tallies.
+        let mut alloc_info = ThreadAllocInfo::current().unwrap();
+
+        // Resets the allocation tallies and returns the previous tallies.
+        let mut take_alloc_tallies =
+            || std::mem::take(unsafe { &mut alloc_info.as_mut().tallies });
+
+        // Start fresh.
+        _ = take_alloc_tallies();
+
+        // Helper to create `ThreadAllocTallyMap` since each operation only
+        // changes `buf` by 1 `i32`.
+        let item_tally =
+            ThreadAllocTally { count: 1, size: size_of::<i32>() as _ };
+        let make_tally_map = |op: AllocOp| {
+            ThreadAllocTallyMap::from_fn(|other_op| {
+                if other_op == op {
+                    item_tally
+                } else {
+                    Default::default()
+                }
+            })
+        };
+
+        // Test zero.
+        let mut buf: Vec<i32> = Vec::new();
+        assert_eq!(take_alloc_tallies(), Default::default());
+
+        // Test allocation.
+        buf.reserve_exact(1);
+        assert_eq!(take_alloc_tallies(), make_tally_map(AllocOp::Alloc));
+
+        // Test grow.
+        buf.reserve_exact(2);
+        assert_eq!(take_alloc_tallies(), make_tally_map(AllocOp::Grow));
+
+        // Test shrink.
+        buf.shrink_to(1);
+        assert_eq!(take_alloc_tallies(), make_tally_map(AllocOp::Shrink));
+
+        // Test dealloc.
+        drop(buf);
+        assert_eq!(take_alloc_tallies(), make_tally_map(AllocOp::Dealloc));
+
+        // Test all of the above together.
+        let mut buf: Vec<i32> = Vec::new();
+        buf.reserve_exact(1); // alloc
+        buf.reserve_exact(2); // grow
+        buf.shrink_to(1); // shrink
+        drop(buf); // dealloc
+        assert_eq!(
+            take_alloc_tallies(),
+            ThreadAllocTallyMap::from_fn(|op| match op {
+                AllocOp::Zeroed | AllocOp::Aligned | AllocOp::Huge => {
+                    Default::default()
+                }
+                _ => item_tally,
+            })
+        );
+    }
+
+    #[test]
+    fn check_max_allocs() {
+        let tallies = ThreadAllocTallyMap::from_fn(|op| match op {
+            AllocOp::Alloc => ThreadAllocTally { count: 4, size: 64 },
+            AllocOp::Grow => ThreadAllocTally { count: 2, size: 32 },
+            AllocOp::Dealloc => ThreadAllocTally { count: 4, size: 64 },
+            _ => ThreadAllocTally::default(),
+        });
+
+        assert_eq!(tallies.alloc_count(), 6);
+
+        assert_eq!(tallies.check_max_allocs(3, 2), Ok(()));
+        assert_eq!(ThreadAllocTallyMap::new().check_max_allocs(0, 1), Ok(()));
+
+        assert_eq!(
+            tallies.check_max_allocs(2, 2).unwrap_err(),
+            "benchmark exceeded `max_allocs = 2` per iteration with 6 allocations over 2 iterations:\n  alloc:   4 (64 bytes)\n  dealloc: 4 (64 bytes)\n  grow:    2 (32 bytes)"
+        );
+    }
+
+    #[test]
+    fn check_max_retained_bytes() {
+        let mut info = ThreadAllocInfo::new();
+        info.current_count = 2;
+        info.current_size = 48;
+
+        assert_eq!(info.check_max_retained_bytes(24, 2), Ok(()));
+
+        assert_eq!(
+            info.check_max_retained_bytes(16, 2).unwrap_err(),
+            "benchmark exceeded `max_retained_bytes = 16` per iteration by retaining 48 bytes in 2 allocations over 2 iterations"
+        );
+
+        // Freeing memory kept by earlier samples is never a failure.
+        info.current_count = -2;
+        info.current_size = -48;
+        assert_eq!(info.check_max_retained_bytes(0, 1), Ok(()));
+    }
+
+    #[test]
+    fn check_leaks() {
+        let mut info = ThreadAllocInfo::new();
+        assert_eq!(info.check_leaks(), Ok(()));
+
+        info.current_count = 1;
+        info.current_size = 24;
+        assert_eq!(
+            info.check_leaks().unwrap_err(),
+            "benchmark leaked 24 bytes in 1 allocation"
+        );
+
+        info.current_count = 2;
+        info.current_size = 48;
+        assert_eq!(
+            info.check_leaks().unwrap_err(),
+            "benchmark leaked 48 bytes in 2 allocations"
+        );
+
+        // Freeing memory kept by earlier runs is not a leak.
+        info.current_count = -1;
+        info.current_size = -24;
+        assert_eq!(info.check_leaks(), Ok(()));
+    }
+
+    #[test]
+    fn alloc_categories() {
+        let config = ProfilerConfig {
+            alloc_categories: true,
+            huge_threshold: 1024,
+            ..ProfilerConfig::DEFAULT
+        };
+
+        let categories = |size: usize, align: usize, is_zeroed: bool| {
+            let layout = Layout::from_size_align(size, align).unwrap();
+            config.categories_of(layout, is_zeroed).count()
+        };
+
+        assert_eq!(categories(8, 8, false), 0);
+        assert_eq!(categories(8, 8, true), 1);
+        assert_eq!(categories(8, 16, false), 0);
+        assert_eq!(categories(8, 32, false), 1);
+        assert_eq!(categories(1023, 8, false), 0);
+        assert_eq!(categories(1024, 8, false), 1);
+        assert_eq!(categories(1024, 64, true), 3);
+
+        // A threshold of 0 disables huge allocations.
+        let config = ProfilerConfig { huge_threshold: 0, ..config };
+        let layout = Layout::from_size_align(0, 8).unwrap();
+        assert_eq!(config.categories_of(layout, false).count(), 0);
+    }
+
+    #[test]
+    fn size_bin_index() {
+        let cases: &[(usize, usize)] =
+            &[(0, 0), (1, 0), (2, 1), (3, 2), (4, 2), (5, 3), (4096, 12)];
+
+        for &(size, index) in cases {
+            assert_eq!(AllocSizeBins::index_of(size), index, "{size}");
+        }
+
+        assert_eq!(
+            AllocSizeBins::index_of(isize::MAX as usize),
+            ALLOC_SIZE_BIN_COUNT - 1
+        );
+    }
+}
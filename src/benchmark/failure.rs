@@ -0,0 +1,46 @@
+//! Failing benchmarks that return errors.
+
+use std::{fmt::Display, panic};
+
+/// Unwind payload for a benchmark that returned an error.
+///
+/// The error is carried by the payload rather than stored elsewhere, so that
+/// concurrent runs do not observe each other's failures.
+pub(crate) struct BenchFailure {
+    /// The error's message.
+    pub message: String,
+}
+
+/// Stops the current benchmark because it returned `error`.
+///
+/// This unwinds without running the panic hook, so the error is only reported
+/// by the benchmark tree.
+#[cold]
+pub(crate) fn fail(error: &dyn Display) -> ! {
+    panic::resume_unwind(Box::new(BenchFailure { message: error.to_string() }))
+}
+
+/// Returns the value of `result`, or fails the current benchmark with its
+/// error.
+///
+/// Errors returned by [`#[divan::bench]`](macro@crate::bench) functions are
+/// checked automatically, but closures passed to [`Bencher`](crate::Bencher)
+/// methods may return any output. This fails the benchmark the same way when a
+/// closure's result is [`Err`], including on
+/// [auxiliary threads](macro@crate::bench#threads).
+///
+/// # Examples
+///
+/// ```
+/// #[divan::bench]
+/// fn parse(bencher: divan::Bencher) {
+///     bencher.bench(|| divan::check(divan::black_box("42").parse::<u64>()));
+/// }
+/// ```
+#[inline]
+pub fn check<T, E: Display>(result: Result<T, E>) -> T {
+    match result {
+        Ok(value) => value,
+        Err(error) => fail(&error),
+    }
+}
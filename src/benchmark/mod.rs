@@ -1,14 +1,16 @@
 use std::{
-    cell::UnsafeCell,
+    cell::{Cell, UnsafeCell},
     fmt,
     mem::{self, MaybeUninit},
     num::NonZeroUsize,
-    sync::Barrier,
+    panic,
+    time::Duration,
 };
 
 use crate::{
     alloc::{
-        AllocOp, AllocOpMap, AllocTally, ThreadAllocInfo, ThreadAllocTally,
+        AllocOp, AllocOpMap, AllocSizeBins, AllocTally, ThreadAllocCountSigned,
+        ThreadAllocInfo, ThreadAllocTally, ThreadAllocTallyMap,
         TotalAllocTallyMap,
     },
     black_box, black_box_drop,
@@ -18,9 +20,15 @@ use crate::{
         KnownCounterKind, MaxCountUInt,
     },
     divan::SharedContext,
-    stats::{RawSample, SampleCollection, Stats, StatsSet, TimeSample},
+    report::{BenchStats, RoleStats},
+    stats::{
+        Distribution, RawSample, SampleCollection, Stats, StatsSet, TimeSample,
+    },
     time::{FineDuration, Timestamp, UntaggedTimestamp},
-    util::{self, sync::SyncWrap},
+    util::{
+        self,
+        sync::{Barrier, SyncWrap},
+    },
 };
 
 #[cfg(test)]
@@ -28,13 +36,24 @@ mod tests;
 
 mod args;
 mod defer;
+mod failure;
 mod options;
+mod pin;
+mod refs;
+mod roles;
 
 use defer::{DeferSlot, DeferStore};
+use roles::RoleAssignment;
+
+pub(crate) use failure::{fail, BenchFailure};
 
 pub use self::{
     args::{BenchArgs, BenchArgsRunner},
+    failure::check,
     options::BenchOptions,
+    pin::CpuPin,
+    refs::{borrowing, RefsOutput},
+    roles::ThreadRole,
 };
 
 pub(crate) const DEFAULT_SAMPLE_COUNT: u32 = 100;
@@ -67,8 +86,10 @@ pub struct Bencher<'a, 'b, C = BencherConfig> {
 ///
 /// This enables configuring `Bencher` using the builder pattern with zero
 /// runtime cost.
-pub struct BencherConfig<GenI = NoInput> {
+pub struct BencherConfig<GenI = NoInput, Setup = fn(), Teardown = fn()> {
     gen_input: GenI,
+    sample_setup: Setup,
+    sample_teardown: Teardown,
 }
 
 /// Public-in-private marker for default `Bencher` input-generation functions.
@@ -77,6 +98,10 @@ pub struct BencherConfig<GenI = NoInput> {
 /// working with `()` unintentionally.
 pub struct NoInput;
 
+/// Public-in-private wrapper for the per-thread state initializer given to
+/// `Bencher::with_thread_state`.
+pub struct ThreadState<F>(F);
+
 impl<C> fmt::Debug for Bencher<'_, '_, C> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Bencher").finish_non_exhaustive()
@@ -86,11 +111,20 @@ impl<C> fmt::Debug for Bencher<'_, '_, C> {
 impl<'a, 'b> Bencher<'a, 'b> {
     #[inline]
     pub(crate) fn new(context: &'a mut BenchContext<'b>) -> Self {
-        Self { context, config: BencherConfig { gen_input: NoInput } }
+        Self {
+            context,
+            config: BencherConfig {
+                gen_input: NoInput,
+                sample_setup: || {},
+                sample_teardown: || {},
+            },
+        }
     }
 }
 
-impl<'a, 'b> Bencher<'a, 'b> {
+impl<'a, 'b, Setup, Teardown>
+    Bencher<'a, 'b, BencherConfig<NoInput, Setup, Teardown>>
+{
     /// Benchmarks a function.
     ///
     /// The function can be benchmarked in parallel using the [`threads`
@@ -110,6 +144,8 @@ impl<'a, 'b> Bencher<'a, 'b> {
     pub fn bench<O, B>(self, benched: B)
     where
         B: Fn() -> O + Sync,
+        Setup: Fn() + Sync,
+        Teardown: Fn() + Sync,
     {
         // Reusing `bench_values` for a zero-sized non-drop input type should
         // have no overhead.
@@ -131,12 +167,114 @@ impl<'a, 'b> Bencher<'a, 'b> {
     pub fn bench_local<O, B>(self, mut benched: B)
     where
         B: FnMut() -> O,
+        Setup: FnMut(),
+        Teardown: FnMut(),
     {
         // Reusing `bench_local_values` for a zero-sized non-drop input type
         // should have no overhead.
         self.with_inputs(|| ()).bench_local_values(|_: ()| benched());
     }
 
+    /// Benchmarks a function that runs differently depending on the role of
+    /// each thread, such as for producer/consumer or reader/writer workloads.
+    ///
+    /// The function is given the index of its thread's role in `roles`.
+    /// Threads are assigned roles based on the [`threads`
+    /// option](macro@crate::bench#threads) as described by [`ThreadRole`].
+    /// Roles that get no threads are not run.
+    ///
+    /// Statistics are reported for all threads together, as well as for each
+    /// role by [`BenchStats::roles`](crate::report::BenchStats::roles).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `roles` is empty, or if roles do not use every thread and
+    /// none were created with [`ThreadRole::rest`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::RwLock;
+    /// use divan::{Bencher, ThreadRole};
+    ///
+    /// #[divan::bench(threads = [2, 4, 8])]
+    /// fn rwlock(bencher: Bencher) {
+    ///     let lock = RwLock::new(0);
+    ///
+    ///     bencher.bench_roles(
+    ///         &[ThreadRole::new("writer", 1), ThreadRole::rest("reader")],
+    ///         |role| match role {
+    ///             0 => *lock.write().unwrap() += 1,
+    ///             _ => _ = divan::black_box(*lock.read().unwrap()),
+    ///         },
+    ///     );
+    /// }
+    /// ```
+    pub fn bench_roles<O, B>(self, roles: &[ThreadRole], benched: B)
+    where
+        B: Fn(usize) -> O + Sync,
+        Setup: Fn() + Sync,
+        Teardown: Fn() + Sync,
+    {
+        let assignment =
+            RoleAssignment::new(roles, self.context.thread_count.get());
+        let role_by_thread = assignment.by_thread.clone();
+        self.context.thread_roles = Some(assignment);
+
+        self.bench(|| benched(role_by_thread[roles::thread_index()]));
+    }
+
+    /// Benchmarks a function that measures its own time.
+    ///
+    /// The function is given the number of iterations to run and returns the
+    /// time taken by all of them. This is useful for timing only part of the
+    /// work done by each iteration, or for using time reported by the system
+    /// under test.
+    ///
+    /// Sample sizes are chosen and `min_time`/`max_time` are respected the same
+    /// way as with [`Bencher::bench`]. The function always runs on the current
+    /// thread, and allocations are not counted.
+    ///
+    /// Like [`Bencher::bench_local`], thread counts set at runtime, such as by
+    /// `--threads`, are ignored.
+    ///
+    /// # Errors
+    ///
+    /// The benchmark fails if its [`threads`](macro@crate::bench#threads)
+    /// option requests multiple threads.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Instant;
+    ///
+    /// #[divan::bench]
+    /// fn bench(bencher: divan::Bencher) {
+    ///     bencher.bench_custom(|iters| {
+    ///         let data: Vec<u64> = (0..1000).collect();
+    ///
+    ///         // Only time summing, not creating `data`.
+    ///         let start = Instant::now();
+    ///         for _ in 0..iters {
+    ///             divan::black_box(&data).iter().sum::<u64>();
+    ///         }
+    ///         start.elapsed()
+    ///     });
+    /// }
+    /// ```
+    pub fn bench_custom<B>(self, benched: B)
+    where
+        B: FnMut(u32) -> Duration,
+        Setup: FnMut(),
+        Teardown: FnMut(),
+    {
+        self.context.bench_loop_custom(
+            self.config.sample_setup,
+            benched,
+            self.config.sample_teardown,
+        );
+    }
+
     /// Generate inputs for the [benchmarked function](#input-bench).
     ///
     /// Time spent generating inputs does not affect benchmark timing.
@@ -164,12 +302,122 @@ impl<'a, 'b> Bencher<'a, 'b> {
     pub fn with_inputs<G>(
         self,
         gen_input: G,
-    ) -> Bencher<'a, 'b, BencherConfig<G>> {
-        Bencher { context: self.context, config: BencherConfig { gen_input } }
+    ) -> Bencher<'a, 'b, BencherConfig<G, Setup, Teardown>> {
+        Bencher {
+            context: self.context,
+            config: BencherConfig {
+                gen_input,
+                sample_setup: self.config.sample_setup,
+                sample_teardown: self.config.sample_teardown,
+            },
+        }
+    }
+
+    /// Creates state for each thread that is then passed by `&mut` to the
+    /// [benchmarked function](#state-bench).
+    ///
+    /// The state is created once on each thread
+    /// [benchmarking in parallel](macro@crate::bench#threads), given the
+    /// index of the thread where 0 is the main thread. This happens before the
+    /// thread's first sample, so time and allocations spent creating state do
+    /// not affect benchmark results. States are dropped on the main thread
+    /// after the benchmark finishes.
+    ///
+    /// This is useful for resources that should not be shared between
+    /// threads, such as buffers, random number generators, or connections.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[divan::bench(threads = [1, 4])]
+    /// fn bench(bencher: divan::Bencher) {
+    ///     bencher
+    ///         .with_thread_state(|thread| {
+    ///             // Create buffer for this thread:
+    ///             vec![thread; 1000]
+    ///         })
+    ///         .bench(|buffer| {
+    ///             buffer.sort();
+    ///             divan::black_box(buffer.len())
+    ///         });
+    /// }
+    /// ```
+    pub fn with_thread_state<F>(
+        self,
+        init_state: F,
+    ) -> Bencher<'a, 'b, BencherConfig<ThreadState<F>, Setup, Teardown>> {
+        Bencher {
+            context: self.context,
+            config: BencherConfig {
+                gen_input: ThreadState(init_state),
+                sample_setup: self.config.sample_setup,
+                sample_teardown: self.config.sample_teardown,
+            },
+        }
     }
 }
 
-impl<'a, 'b, GenI> Bencher<'a, 'b, BencherConfig<GenI>> {
+/// <span id="state-bench"></span> Benchmark over [per-thread state](Self::with_thread_state).
+impl<'a, 'b, F, Setup, Teardown>
+    Bencher<'a, 'b, BencherConfig<ThreadState<F>, Setup, Teardown>>
+{
+    /// Benchmarks a function over the current thread's state.
+    ///
+    /// The function is given `&mut` access to the state created by
+    /// [`Bencher::with_thread_state`] for the thread it runs on.
+    pub fn bench<S, O, B>(self, benched: B)
+    where
+        F: Fn(usize) -> S + Sync,
+        S: Send,
+        B: Fn(&mut S) -> O + Sync,
+        Setup: Fn() + Sync,
+        Teardown: Fn() + Sync,
+    {
+        let BencherConfig {
+            gen_input: ThreadState(init_state),
+            sample_setup,
+            sample_teardown,
+        } = self.config;
+
+        // SAFETY: Each thread only accesses the state at its own index.
+        let states: Vec<SyncWrap<UnsafeCell<Option<S>>>> =
+            (0..self.context.thread_count.get())
+                .map(|_| unsafe { SyncWrap::new(UnsafeCell::new(None)) })
+                .collect();
+
+        let thread_state = || {
+            let thread = roles::thread_index();
+
+            // SAFETY: The state is only accessed by the thread at its index.
+            (thread, unsafe { &mut *states[thread].get() })
+        };
+
+        Bencher {
+            context: self.context,
+            config: BencherConfig {
+                gen_input: NoInput,
+                sample_setup: || {
+                    let (thread, state) = thread_state();
+                    state.get_or_insert_with(|| init_state(thread));
+
+                    sample_setup();
+                },
+                sample_teardown,
+            },
+        }
+        .bench(|| {
+            let (_, state) = thread_state();
+
+            // SAFETY: Per-sample setup creates the state before the thread
+            // runs the benchmarked function.
+            benched(unsafe { state.as_mut().unwrap_unchecked() })
+        });
+    }
+}
+
+impl<'a, 'b, GenI, Setup, Teardown>
+    Bencher<'a, 'b, BencherConfig<GenI, Setup, Teardown>>
+{
     /// Assign a [`Counter`] for all iterations of the benchmarked function.
     ///
     /// This will either:
@@ -208,10 +456,131 @@ impl<'a, 'b, GenI> Bencher<'a, 'b, BencherConfig<GenI>> {
         self.context.counters.set_counter(counter);
         self
     }
+
+    /// Fails the benchmark if any iteration of the benchmarked function
+    /// allocates more than `max_allocs` times.
+    ///
+    /// Allocations are only counted when [`AllocProfiler`](crate::AllocProfiler)
+    /// is the [global allocator](macro@global_allocator). Every `alloc`, `grow`,
+    /// and `shrink` operation in the timed section counts as an allocation.
+    ///
+    /// This check also runs in [`--test`](crate::Divan::test_benches) mode,
+    /// which makes it useful for ensuring code paths stay allocation-free.
+    ///
+    /// If context is not needed, the limit can instead be set via
+    /// [`#[divan::bench(max_allocs = ...)]`](macro@crate::bench#max_allocs).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[divan::bench]
+    /// fn sum(bencher: divan::Bencher) {
+    ///     let values: Vec<u64> = (0..100).collect();
+    ///
+    ///     bencher.assert_allocs(0).bench(|| {
+    ///         divan::black_box(&values).iter().sum::<u64>()
+    ///     });
+    /// }
+    /// ```
+    pub fn assert_allocs(self, max_allocs: u64) -> Self {
+        self.context.max_allocs = Some(max_allocs);
+        self
+    }
+
+    /// Calls a function before each sample of the benchmarked function.
+    ///
+    /// This is useful for preparing state that each sample relies on, such as
+    /// clearing a cache or resetting an arena. Unlike
+    /// [generated inputs](Self::with_inputs), this runs once per sample rather
+    /// than once per iteration.
+    ///
+    /// Time spent in this function does not affect benchmark timing or
+    /// allocation counts. When [`skip_ext_time`](macro@crate::bench#skip_ext_time)
+    /// is set, it also does not count towards
+    /// [`min_time`](macro@crate::bench#min_time).
+    ///
+    /// When [benchmarking in parallel](macro@crate::bench#threads), this is
+    /// called on each thread before it runs its sample.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{cell::RefCell, collections::HashMap};
+    ///
+    /// #[divan::bench]
+    /// fn bench(bencher: divan::Bencher) {
+    ///     let cache = RefCell::new(HashMap::<u64, u64>::new());
+    ///
+    ///     bencher
+    ///         .with_sample_setup(|| cache.borrow_mut().clear())
+    ///         .bench_local(|| {
+    ///             let mut cache = cache.borrow_mut();
+    ///             *cache.entry(divan::black_box(42)).or_insert(42 * 42)
+    ///         });
+    /// }
+    /// ```
+    pub fn with_sample_setup<S>(
+        self,
+        sample_setup: S,
+    ) -> Bencher<'a, 'b, BencherConfig<GenI, S, Teardown>> {
+        Bencher {
+            context: self.context,
+            config: BencherConfig {
+                gen_input: self.config.gen_input,
+                sample_setup,
+                sample_teardown: self.config.sample_teardown,
+            },
+        }
+    }
+
+    /// Calls a function after each sample of the benchmarked function.
+    ///
+    /// This runs after all of the sample's inputs and outputs are dropped, and
+    /// is useful for cleaning up state that each sample leaves behind, such as
+    /// truncating a temporary file.
+    ///
+    /// Time spent in this function does not affect benchmark timing or
+    /// allocation counts. When [`skip_ext_time`](macro@crate::bench#skip_ext_time)
+    /// is set, it also does not count towards
+    /// [`min_time`](macro@crate::bench#min_time).
+    ///
+    /// When [benchmarking in parallel](macro@crate::bench#threads), this is
+    /// called on each thread after it runs its sample.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::cell::RefCell;
+    ///
+    /// #[divan::bench]
+    /// fn bench(bencher: divan::Bencher) {
+    ///     let log = RefCell::new(Vec::<u64>::new());
+    ///
+    ///     bencher
+    ///         .with_sample_teardown(|| log.borrow_mut().clear())
+    ///         .bench_local(|| {
+    ///             log.borrow_mut().push(divan::black_box(42));
+    ///         });
+    /// }
+    /// ```
+    pub fn with_sample_teardown<T>(
+        self,
+        sample_teardown: T,
+    ) -> Bencher<'a, 'b, BencherConfig<GenI, Setup, T>> {
+        Bencher {
+            context: self.context,
+            config: BencherConfig {
+                gen_input: self.config.gen_input,
+                sample_setup: self.config.sample_setup,
+                sample_teardown,
+            },
+        }
+    }
 }
 
 /// <span id="input-bench"></span> Benchmark over [generated inputs](Self::with_inputs).
-impl<'a, 'b, I, GenI> Bencher<'a, 'b, BencherConfig<GenI>>
+impl<'a, 'b, I, GenI, Setup, Teardown>
+    Bencher<'a, 'b, BencherConfig<GenI, Setup, Teardown>>
 where
     GenI: FnMut() -> I,
 {
@@ -339,9 +708,11 @@ where
     where
         B: Fn(I) -> O + Sync,
         GenI: Fn() -> I + Sync,
+        Setup: Fn() + Sync,
+        Teardown: Fn() + Sync,
     {
         self.context.bench_loop_threaded(
-            self.config.gen_input,
+            self.config,
             |input| {
                 // SAFETY: Input is guaranteed to be initialized and not
                 // currently referenced by anything else.
@@ -380,9 +751,11 @@ where
     pub fn bench_local_values<O, B>(self, mut benched: B)
     where
         B: FnMut(I) -> O,
+        Setup: FnMut(),
+        Teardown: FnMut(),
     {
         self.context.bench_loop_local(
-            self.config.gen_input,
+            self.config,
             |input| {
                 // SAFETY: Input is guaranteed to be initialized and not
                 // currently referenced by anything else.
@@ -401,6 +774,9 @@ where
     /// Per-iteration means the benchmarked function is called exactly once for
     /// each generated input.
     ///
+    /// If the output needs to borrow from the input, use
+    /// [`Bencher::bench_refs_borrowing`] instead.
+    ///
     /// # Examples
     ///
     /// ```
@@ -421,10 +797,11 @@ where
     where
         B: Fn(&mut I) -> O + Sync,
         GenI: Fn() -> I + Sync,
+        Setup: Fn() + Sync,
+        Teardown: Fn() + Sync,
     {
-        // TODO: Allow `O` to reference `&mut I` as long as `I` outlives `O`.
         self.context.bench_loop_threaded(
-            self.config.gen_input,
+            self.config,
             |input| {
                 // SAFETY: Input is guaranteed to be initialized and not
                 // currently referenced by anything else.
@@ -447,6 +824,9 @@ where
     /// Per-iteration means the benchmarked function is called exactly once for
     /// each generated input.
     ///
+    /// If the output needs to borrow from the input, use
+    /// [`Bencher::bench_local_refs_borrowing`] instead.
+    ///
     /// # Examples
     ///
     /// ```
@@ -466,10 +846,11 @@ where
     pub fn bench_local_refs<O, B>(self, mut benched: B)
     where
         B: FnMut(&mut I) -> O,
+        Setup: FnMut(),
+        Teardown: FnMut(),
     {
-        // TODO: Allow `O` to reference `&mut I` as long as `I` outlives `O`.
         self.context.bench_loop_local(
-            self.config.gen_input,
+            self.config,
             |input| {
                 // SAFETY: Input is guaranteed to be initialized and not
                 // currently referenced by anything else.
@@ -485,6 +866,160 @@ where
             },
         );
     }
+
+    /// Benchmarks a function over per-iteration [generated inputs](Self::with_inputs),
+    /// provided by-reference, whose output may borrow from the input.
+    ///
+    /// This is like [`Bencher::bench_refs`], except the returned value may
+    /// reference `&mut I`, such as a parser returning views into its buffer.
+    /// Outputs are always dropped before their inputs.
+    ///
+    /// Closures must be wrapped in [`divan::borrowing`](crate::borrowing) to
+    /// infer that their output borrows from their input.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// fn first_word(s: &mut String) -> &str {
+    ///     s.make_ascii_uppercase();
+    ///     s.split(' ').next().unwrap_or_default()
+    /// }
+    ///
+    /// #[divan::bench]
+    /// fn bench(bencher: divan::Bencher) {
+    ///     bencher
+    ///         .with_inputs(|| {
+    ///             // Generate input:
+    ///             String::from("hello world")
+    ///         })
+    ///         .bench_refs_borrowing(first_word);
+    /// }
+    /// ```
+    pub fn bench_refs_borrowing<B>(self, benched: B)
+    where
+        B: for<'i> RefsOutput<'i, I>
+            + for<'i> Fn(&'i mut I) -> <B as RefsOutput<'i, I>>::Output
+            + Sync,
+        GenI: Fn() -> I + Sync,
+        Setup: Fn() + Sync,
+        Teardown: Fn() + Sync,
+    {
+        // `'i` outlives the benchmark loop, during which every output is
+        // dropped before its input. `B` is generic over all lifetimes, so it
+        // cannot retain the extended reference.
+        fn bench<'i, I: 'i, GenI, Setup, Teardown, B>(
+            context: &mut BenchContext,
+            config: BencherConfig<GenI, Setup, Teardown>,
+            benched: B,
+        ) where
+            B: for<'j> RefsOutput<'j, I>
+                + for<'j> Fn(&'j mut I) -> <B as RefsOutput<'j, I>>::Output
+                + Sync,
+            GenI: Fn() -> I + Sync,
+            Setup: Fn() + Sync,
+            Teardown: Fn() + Sync,
+        {
+            context.bench_loop_threaded(
+                config,
+                |input| {
+                    // SAFETY: Input is guaranteed to be initialized and not
+                    // currently referenced by anything else. The output is
+                    // dropped before the input.
+                    let input: &'i mut I =
+                        unsafe { &mut *(*input.get()).as_mut_ptr() };
+
+                    benched(input)
+                },
+                // Input ownership was not transferred to `benched`.
+                |input| {
+                    // SAFETY: This function is called after `benched` outputs
+                    // are dropped, so we have exclusive access.
+                    unsafe { (*input.get()).assume_init_drop() }
+                },
+            );
+        }
+
+        bench(self.context, self.config, benched);
+    }
+
+    /// Benchmarks a function over per-iteration [generated inputs](Self::with_inputs),
+    /// provided by-reference, whose output may borrow from the input.
+    ///
+    /// This is like [`Bencher::bench_local_refs`], except the returned value
+    /// may reference `&mut I`, such as a parser returning views into its
+    /// buffer. Outputs are always dropped before their inputs.
+    ///
+    /// Closures must be wrapped in [`divan::borrowing`](crate::borrowing) to
+    /// infer that their output borrows from their input.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// fn first_word(s: &mut String) -> &str {
+    ///     s.make_ascii_uppercase();
+    ///     s.split(' ').next().unwrap_or_default()
+    /// }
+    ///
+    /// #[divan::bench]
+    /// fn bench(bencher: divan::Bencher) {
+    ///     bencher
+    ///         .with_inputs(|| {
+    ///             // Generate input:
+    ///             String::from("hello world")
+    ///         })
+    ///         .bench_local_refs_borrowing(first_word);
+    /// }
+    /// ```
+    pub fn bench_local_refs_borrowing<B>(self, benched: B)
+    where
+        B: for<'i> RefsOutput<'i, I>
+            + for<'i> FnMut(&'i mut I) -> <B as RefsOutput<'i, I>>::Output,
+        Setup: FnMut(),
+        Teardown: FnMut(),
+    {
+        // See `bench_refs_borrowing` for why extending `'i` is sound.
+        fn bench<'i, I: 'i, GenI, Setup, Teardown, B>(
+            context: &mut BenchContext,
+            config: BencherConfig<GenI, Setup, Teardown>,
+            mut benched: B,
+        ) where
+            B: for<'j> RefsOutput<'j, I>
+                + for<'j> FnMut(&'j mut I) -> <B as RefsOutput<'j, I>>::Output,
+            GenI: FnMut() -> I,
+            Setup: FnMut(),
+            Teardown: FnMut(),
+        {
+            context.bench_loop_local(
+                config,
+                |input| {
+                    // SAFETY: Input is guaranteed to be initialized and not
+                    // currently referenced by anything else. The output is
+                    // dropped before the input.
+                    let input: &'i mut I =
+                        unsafe { &mut *(*input.get()).as_mut_ptr() };
+
+                    benched(input)
+                },
+                // Input ownership was not transferred to `benched`.
+                |input| {
+                    // SAFETY: This function is called after `benched` outputs
+                    // are dropped, so we have exclusive access.
+                    unsafe { (*input.get()).assume_init_drop() }
+                },
+            );
+        }
+
+        bench(self.context, self.config, benched);
+    }
+}
+
+/// Timing of a round of samples recorded by `BenchContext::sample_loop`.
+struct SampleRound {
+    /// The duration of the slowest sample.
+    slowest: FineDuration,
+
+    /// When the last sample ended.
+    end: Timestamp,
 }
 
 /// State machine for how the benchmark is being run.
@@ -547,11 +1082,32 @@ pub(crate) struct BenchContext<'a> {
     /// thread and not spawn any threads.
     pub thread_count: NonZeroUsize,
 
+    /// Whether `thread_count` was set by the benchmark's own attribute rather
+    /// than by runtime options.
+    pub is_attr_thread_count: bool,
+
+    /// The CPU to pin each thread to, by thread index. Threads beyond these are
+    /// not pinned.
+    pub pin_cpus: &'a [usize],
+
+    /// The CPU that each thread was pinned to, by thread index.
+    pub pinned_cpus: Vec<usize>,
+
     /// Recorded samples.
     samples: SampleCollection,
 
     /// Per-iteration counters grouped by sample.
     counters: CounterCollection,
+
+    /// The maximum number of allocations allowed per iteration.
+    max_allocs: Option<u64>,
+
+    /// Roles of each thread if benchmarked with `Bencher::bench_roles`.
+    thread_roles: Option<RoleAssignment>,
+
+    /// Whether the same benchmarked function ran on each of `thread_count`
+    /// threads, so that the run can be compared to other thread counts.
+    pub is_thread_scalable: bool,
 }
 
 impl<'a> BenchContext<'a> {
@@ -565,9 +1121,15 @@ impl<'a> BenchContext<'a> {
             shared_context,
             options,
             thread_count,
+            is_attr_thread_count: false,
+            pin_cpus: &[],
+            pinned_cpus: Vec::new(),
             did_run: false,
             samples: SampleCollection::default(),
             counters: options.counters.to_collection(),
+            max_allocs: options.max_allocs,
+            thread_roles: None,
+            is_thread_scalable: false,
         }
     }
 
@@ -578,24 +1140,36 @@ impl<'a> BenchContext<'a> {
     /// See `bench_loop_threaded`.
     pub fn bench_loop_local<I, O>(
         &mut self,
-        gen_input: impl FnMut() -> I,
+        config: BencherConfig<impl FnMut() -> I, impl FnMut(), impl FnMut()>,
         benched: impl FnMut(&UnsafeCell<MaybeUninit<I>>) -> O,
         drop_input: impl Fn(&UnsafeCell<MaybeUninit<I>>),
     ) {
         // SAFETY: Closures are guaranteed to run on the current thread, so they
         // can safely be mutable and non-`Sync`.
         unsafe {
-            let gen_input = SyncWrap::new(UnsafeCell::new(gen_input));
+            let gen_input = SyncWrap::new(UnsafeCell::new(config.gen_input));
+            let sample_setup =
+                SyncWrap::new(UnsafeCell::new(config.sample_setup));
+            let sample_teardown =
+                SyncWrap::new(UnsafeCell::new(config.sample_teardown));
             let benched = SyncWrap::new(UnsafeCell::new(benched));
             let drop_input = SyncWrap::new(drop_input);
 
             self.thread_count = NonZeroUsize::MIN;
             self.bench_loop_threaded::<I, O>(
-                || (*gen_input.get())(),
+                BencherConfig {
+                    gen_input: || (*gen_input.get())(),
+                    sample_setup: || (*sample_setup.get())(),
+                    sample_teardown: || (*sample_teardown.get())(),
+                },
                 |input| (*benched.get())(input),
                 |input| drop_input(input),
-            )
+            );
         }
+
+        // Local benchmarks ignore the requested thread count, so they cannot be
+        // compared across thread counts.
+        self.is_thread_scalable = false;
     }
 
     /// Runs the multi-threaded loop for benchmarking `benched`.
@@ -616,17 +1190,38 @@ impl<'a> BenchContext<'a> {
     ///   escaped references to `I`.
     fn bench_loop_threaded<I, O>(
         &mut self,
-        gen_input: impl Fn() -> I + Sync,
+        config: BencherConfig<
+            impl Fn() -> I + Sync,
+            impl Fn() + Sync,
+            impl Fn() + Sync,
+        >,
         benched: impl Fn(&UnsafeCell<MaybeUninit<I>>) -> O + Sync,
         drop_input: impl Fn(&UnsafeCell<MaybeUninit<I>>) + Sync,
     ) {
         self.did_run = true;
+        self.is_thread_scalable = self.thread_roles.is_none();
+
+        // Bypass `AllocProfiler` on benchmarking threads if requested.
+        let is_profiling = self.options.alloc_profile.unwrap_or(true);
+
+        // Profiling state is restored even if the benchmark unwinds within a
+        // sample, such as by returning an error, so that the rest of the run is
+        // unaffected.
+        let _restore_profiling = crate::util::defer(|| {
+            crate::alloc::IGNORE_ALLOC.set(false);
+            crate::alloc::global::set_sampling(false);
+            crate::alloc::call_site::set_active(false);
+            _ = crate::alloc::global::stop();
+        });
 
-        let mut current_mode = self.initial_mode();
-        let is_test = current_mode.is_test();
+        let is_test = self.initial_mode().is_test();
 
-        let record_sample =
-            self.sample_recorder(gen_input, benched, drop_input);
+        // When checking for leaks, the first run only warms up one-time
+        // initialization, such as lazily-initialized statics.
+        let check_leaks = is_test && self.shared_context.check_leaks;
+        let mut is_leak_warmup = check_leaks;
+
+        let record_sample = self.sample_recorder(config, benched, drop_input);
 
         let thread_count = self.thread_count.get();
         let aux_thread_count = thread_count - 1;
@@ -636,73 +1231,12 @@ impl<'a> BenchContext<'a> {
         // Per-thread sample info returned by `record_sample`. These are
         // processed locally to emit user-facing sample info. As a result, this
         // only contains `thread_count` many elements at a time.
-        let mut raw_samples = Vec::<Option<RawSample>>::new();
-
-        // The time spent benchmarking, in picoseconds.
-        //
-        // Unless `skip_ext_time` is set, this includes time external to
-        // `benched`, such as time spent generating inputs and running drop.
-        let mut elapsed_picos: u128 = 0;
-
-        // The minimum time for benchmarking, in picoseconds.
-        let min_picos = self.options.min_time().picos;
-
-        // The remaining time left for benchmarking, in picoseconds.
-        let max_picos = self.options.max_time().picos;
-
-        // Don't bother running if user specifies 0 max time or 0 samples.
-        if max_picos == 0 || !self.options.has_samples() {
-            return;
-        }
-
-        let timer = self.shared_context.timer;
-        let timer_kind = timer.kind();
-
-        let mut rem_samples = if current_mode.is_collect() {
-            Some(self.options.sample_count.unwrap_or(DEFAULT_SAMPLE_COUNT))
-        } else {
-            None
-        };
-
-        // Only measure precision if we need to tune sample size.
-        let timer_precision = if current_mode.is_tune() {
-            timer.precision()
-        } else {
-            FineDuration::default()
-        };
-
-        if !is_test {
-            self.samples
-                .time_samples
-                .reserve(self.options.sample_count.unwrap_or(1) as usize);
-        }
-
-        let skip_ext_time = self.options.skip_ext_time.unwrap_or_default();
-        let initial_start = if skip_ext_time {
-            None
-        } else {
-            Some(Timestamp::start(timer_kind))
-        };
-
-        let bench_overheads = timer.bench_overheads();
-
-        while {
-            // Conditions for when sampling is over:
-            if elapsed_picos >= max_picos {
-                // Depleted the benchmarking time budget. This is a strict
-                // condition regardless of sample count and minimum time.
-                false
-            } else if rem_samples.unwrap_or(1) > 0 {
-                // More samples expected.
-                true
-            } else {
-                // Continue if we haven't reached the time floor.
-                elapsed_picos < min_picos
-            }
-        } {
-            let sample_size = current_mode.sample_size();
-            self.samples.sample_size = sample_size;
+        let mut raw_samples = Vec::<Option<RawSample>>::new();
+
+        let timer = self.shared_context.timer;
+        let bench_overheads = timer.bench_overheads();
 
+        self.sample_loop(|this, sample_size, timer_precision| {
             let barrier = if is_single_thread {
                 None
             } else {
@@ -720,7 +1254,7 @@ impl<'a> BenchContext<'a> {
                         // SAFETY: The `I` type cannot change since `with_inputs`
                         // cannot be called more than once on the same `Bencher`.
                         if let Some(count) = unsafe {
-                            self.counters.get_input_count(counter_kind, input)
+                            this.counters.get_input_count(counter_kind, input)
                         } {
                             let total =
                                 &mut counter_totals[counter_kind as usize];
@@ -730,31 +1264,57 @@ impl<'a> BenchContext<'a> {
                 };
 
                 // Sample loop:
-                let ([start, end], alloc_info) = record_sample(
+                let ([start, end], alloc_info, call_sites) = record_sample(
                     sample_size as usize,
                     barrier.as_ref(),
                     &mut count_input,
                 );
 
-                RawSample { start, end, timer, alloc_info, counter_totals }
+                RawSample {
+                    start,
+                    end,
+                    timer,
+                    alloc_info,
+                    call_sites,
+                    counter_totals,
+                }
             };
 
             // Sample loop:
             raw_samples.clear();
-            self.shared_context.thread_pool.par_extend(
+            let result = this.shared_context.thread_pool.par_extend(
                 &mut raw_samples,
                 aux_thread_count,
-                |_| record_sample(),
+                |thread| {
+                    roles::set_thread_index(thread);
+                    crate::alloc::set_profiling(is_profiling);
+
+                    // Reset this thread's profiling state for other
+                    // benchmarks. If it panics, also release other threads
+                    // waiting on it.
+                    let _reset_profiling = util::defer(|| {
+                        if std::thread::panicking() {
+                            if let Some(barrier) = &barrier {
+                                barrier.poison();
+                            }
+                            crate::alloc::global::set_sampling(false);
+                            crate::alloc::call_site::set_active(false);
+                        }
+                        crate::alloc::set_profiling(true);
+                    });
+
+                    record_sample()
+                },
             );
 
+            // Continue unwinding with the original panic or error of any
+            // thread.
+            if let Err(payload) = result {
+                panic::resume_unwind(payload);
+            }
+
             // Convert `&[Option<RawSample>]` to `&[Sample]`.
             let raw_samples: &[RawSample] = {
-                if let Some(thread) = raw_samples.iter().enumerate().find_map(
-                    |(thread, sample)| sample.is_none().then_some(thread),
-                ) {
-                    panic!("Divan benchmarking thread {thread} panicked");
-                }
-
                 unsafe {
                     assert_eq!(
                         mem::size_of::<RawSample>(),
@@ -767,44 +1327,62 @@ impl<'a> BenchContext<'a> {
                 }
             };
 
-            // If testing, exit the benchmarking loop immediately after timing a
-            // single run.
-            if is_test {
-                break;
+            // Fail if any thread allocated more than allowed.
+            if let Some(max_allocs) = this.max_allocs {
+                for raw_sample in raw_samples {
+                    if let Err(message) = raw_sample
+                        .alloc_info
+                        .tallies
+                        .check_max_allocs(max_allocs, sample_size)
+                    {
+                        crate::alloc::IGNORE_ALLOC.set(false);
+                        panic!("{message}");
+                    }
+                }
             }
 
-            let slowest_sample =
-                raw_samples.iter().max_by_key(|s| s.duration()).unwrap();
-            let slowest_time = slowest_sample.duration();
-
-            // TODO: Make tuning be less influenced by early runs. Currently if
-            // early runs are very quick but later runs are slow, benchmarking
-            // will take a very long time.
-            //
-            // TODO: Make `sample_size` consider time generating inputs and
-            // dropping inputs/outputs. Currently benchmarks like
-            // `Bencher::bench_refs(String::clear)` take a very long time.
-            if current_mode.is_tune() {
-                // Clear previous smaller samples.
-                self.samples.clear();
-                self.counters.clear_input_counts();
+            // Fail if any thread retained more memory than allowed.
+            if let Some(max_bytes) = this.options.max_retained_bytes {
+                for raw_sample in raw_samples {
+                    if let Err(message) = raw_sample
+                        .alloc_info
+                        .check_max_retained_bytes(max_bytes, sample_size)
+                    {
+                        crate::alloc::IGNORE_ALLOC.set(false);
+                        panic!("{message}");
+                    }
+                }
+            }
 
-                // If within 100x timer precision, continue tuning.
-                let precision_multiple =
-                    slowest_time.picos / timer_precision.picos;
-                if precision_multiple <= 100 {
-                    current_mode =
-                        BenchMode::Tune { sample_size: sample_size * 2 };
-                } else {
-                    current_mode = BenchMode::Collect { sample_size };
-                    rem_samples = Some(
-                        self.options
-                            .sample_count
-                            .unwrap_or(DEFAULT_SAMPLE_COUNT),
-                    );
+            // Fail if any thread did not free all memory it allocated.
+            if check_leaks && !is_leak_warmup {
+                for raw_sample in raw_samples {
+                    if let Err(message) = raw_sample.alloc_info.check_leaks() {
+                        crate::alloc::IGNORE_ALLOC.set(false);
+                        panic!("{message}");
+                    }
                 }
             }
 
+            if is_leak_warmup {
+                is_leak_warmup = false;
+                return None;
+            }
+
+            let round = SampleRound {
+                slowest: raw_samples
+                    .iter()
+                    .map(RawSample::duration)
+                    .max()
+                    .unwrap(),
+                end: raw_samples.iter().map(|s| s.end).max().unwrap(),
+            };
+
+            // Samples are not recorded when testing.
+            if is_test {
+                return Some(round);
+            }
+
             // Returns the sample's duration adjusted for overhead.
             let sample_duration_sub_overhead = |raw_sample: &RawSample| {
                 let overhead = bench_overheads
@@ -821,22 +1399,32 @@ impl<'a> BenchContext<'a> {
             };
 
             for raw_sample in raw_samples {
-                let sample_index = self.samples.time_samples.len();
+                let sample_index = this.samples.time_samples.len();
 
-                self.samples.time_samples.push(TimeSample {
+                this.samples.time_samples.push(TimeSample {
                     duration: sample_duration_sub_overhead(raw_sample),
                 });
 
-                if !raw_sample.alloc_info.tallies.is_empty() {
-                    self.samples.alloc_info_by_sample.insert(
+                if !raw_sample.alloc_info.tallies.is_empty()
+                    || raw_sample.alloc_info.current_size != 0
+                {
+                    this.samples.alloc_info_by_sample.insert(
                         sample_index as u32,
                         raw_sample.alloc_info.clone(),
                     );
                 }
 
+                for (call_site, count) in &raw_sample.call_sites {
+                    *this
+                        .samples
+                        .alloc_call_sites
+                        .entry(call_site.clone())
+                        .or_default() += count;
+                }
+
                 // Insert per-input counter information.
                 for counter_kind in KnownCounterKind::ALL {
-                    if !self.counters.uses_input_counts(counter_kind) {
+                    if !this.counters.uses_input_counts(counter_kind) {
                         continue;
                     }
 
@@ -848,61 +1436,285 @@ impl<'a> BenchContext<'a> {
                     let per_iter_count =
                         (total_count / sample_size as u128) as MaxCountUInt;
 
-                    self.counters.push_counter(AnyCounter::known(
+                    this.counters.push_counter(AnyCounter::known(
                         counter_kind,
                         per_iter_count,
                     ));
                 }
+            }
+
+            Some(round)
+        });
+
+        // Reset flag for ignoring allocations.
+        crate::alloc::IGNORE_ALLOC.set(false);
+    }
+
+    /// Runs the single-threaded loop for benchmarking `benched`, which returns
+    /// the time taken by the given number of iterations.
+    ///
+    /// Like `bench_loop_local`, this ignores thread counts set by runtime
+    /// options and runs on the current thread. Thread counts set by the
+    /// benchmark's attribute instead fail the benchmark, since they cannot be
+    /// honored.
+    pub fn bench_loop_custom(
+        &mut self,
+        mut sample_setup: impl FnMut(),
+        mut benched: impl FnMut(u32) -> Duration,
+        mut sample_teardown: impl FnMut(),
+    ) {
+        let thread_count = self.thread_count;
+        if thread_count.get() > 1 && self.is_attr_thread_count {
+            fail(&format_args!(
+                "`Bencher::bench_custom` runs on the current thread and cannot \
+                 be benchmarked with `threads = {thread_count}`"
+            ));
+        }
+
+        self.thread_count = NonZeroUsize::MIN;
+
+        self.did_run = true;
+
+        let is_test = self.initial_mode().is_test();
+        let timer = self.shared_context.timer;
+
+        self.sample_loop(|this, sample_size, timer_precision| {
+            sample_setup();
+            let duration = FineDuration::from(benched(sample_size));
+            sample_teardown();
+
+            // Samples are not recorded when testing.
+            if !is_test {
+                this.samples.time_samples.push(TimeSample {
+                    duration: duration.clamp_to(timer_precision),
+                });
+            }
+
+            Some(SampleRound {
+                slowest: duration,
+                end: Timestamp::start(timer.kind()),
+            })
+        });
+
+        // Custom benchmarks ignore the requested thread count, so they cannot
+        // be compared across thread counts.
+        self.is_thread_scalable = false;
+    }
+
+    /// Records rounds of samples until the sample count and time budget are
+    /// met, after tuning the sample size if it is not set.
+    ///
+    /// `record_round` records a round of samples of the given size into
+    /// `self.samples`, with times clamped to the given timer precision. It
+    /// returns `None` for warmup rounds, which are not counted.
+    fn sample_loop(
+        &mut self,
+        mut record_round: impl FnMut(
+            &mut Self,
+            u32,
+            FineDuration,
+        ) -> Option<SampleRound>,
+    ) {
+        let mut current_mode = self.initial_mode();
+        let is_test = current_mode.is_test();
+
+        // The time spent benchmarking, in picoseconds.
+        //
+        // Unless `skip_ext_time` is set, this includes time external to the
+        // measured section, such as time spent generating inputs and running
+        // drop.
+        let mut elapsed_picos: u128 = 0;
+
+        // The minimum time for benchmarking, in picoseconds.
+        let min_picos = self.options.min_time().picos;
+
+        // The remaining time left for benchmarking, in picoseconds.
+        let max_picos = self.options.max_time().picos;
+
+        // Don't bother running if user specifies 0 max time or 0 samples.
+        if max_picos == 0 || !self.options.has_samples() {
+            return;
+        }
+
+        let timer = self.shared_context.timer;
+        let timer_kind = timer.kind();
+
+        let mut rem_samples = if current_mode.is_collect() {
+            Some(self.options.sample_count.unwrap_or(DEFAULT_SAMPLE_COUNT))
+        } else {
+            None
+        };
+
+        // Only measure precision if we need to tune sample size.
+        let timer_precision = if current_mode.is_tune() {
+            timer.precision()
+        } else {
+            FineDuration::default()
+        };
+
+        if !is_test {
+            self.samples
+                .time_samples
+                .reserve(self.options.sample_count.unwrap_or(1) as usize);
+        }
+
+        let skip_ext_time = self.options.skip_ext_time.unwrap_or_default();
+        let initial_start = if skip_ext_time {
+            None
+        } else {
+            Some(Timestamp::start(timer_kind))
+        };
+
+        // Keep threads on their CPUs across all samples.
+        let shared_context = self.shared_context;
+        let (pinned_cpus, _unpin_threads) = pin::pin_threads(
+            &shared_context.thread_pool,
+            self.pin_cpus,
+            self.thread_count.get(),
+        );
+        self.pinned_cpus = pinned_cpus;
+
+        while {
+            // Conditions for when sampling is over:
+            if elapsed_picos >= max_picos {
+                // Depleted the benchmarking time budget. This is a strict
+                // condition regardless of sample count and minimum time.
+                false
+            } else if rem_samples.unwrap_or(1) > 0 {
+                // More samples expected.
+                true
+            } else {
+                // Continue if we haven't reached the time floor.
+                elapsed_picos < min_picos
+            }
+        } {
+            let sample_size = current_mode.sample_size();
+            self.samples.sample_size = sample_size;
+
+            // Clear previous smaller samples.
+            if current_mode.is_tune() {
+                self.samples.clear();
+                self.counters.clear_input_counts();
+            }
+
+            let prev_sample_count = self.samples.time_samples.len();
+
+            let Some(round) = record_round(self, sample_size, timer_precision)
+            else {
+                continue;
+            };
+
+            // If testing, exit the benchmarking loop immediately after timing a
+            // single run.
+            if is_test {
+                break;
+            }
 
-                if let Some(rem_samples) = &mut rem_samples {
-                    *rem_samples = rem_samples.saturating_sub(1);
+            // TODO: Make tuning be less influenced by early runs. Currently if
+            // early runs are very quick but later runs are slow, benchmarking
+            // will take a very long time.
+            //
+            // TODO: Make `sample_size` consider time generating inputs and
+            // dropping inputs/outputs. Currently benchmarks like
+            // `Bencher::bench_refs(String::clear)` take a very long time.
+            if current_mode.is_tune() {
+                let precision_multiple =
+                    round.slowest.picos / timer_precision.picos;
+
+                // If within 100x timer precision, continue tuning. Stop if the
+                // sample size cannot grow, such as when times do not grow with
+                // iterations.
+                match sample_size.checked_mul(2) {
+                    Some(sample_size) if precision_multiple <= 100 => {
+                        current_mode = BenchMode::Tune { sample_size };
+                    }
+                    _ => {
+                        current_mode = BenchMode::Collect { sample_size };
+                        rem_samples = Some(
+                            self.options
+                                .sample_count
+                                .unwrap_or(DEFAULT_SAMPLE_COUNT),
+                        );
+                    }
                 }
             }
 
+            if let Some(rem_samples) = &mut rem_samples {
+                let new_samples =
+                    self.samples.time_samples.len() - prev_sample_count;
+                *rem_samples = rem_samples.saturating_sub(new_samples as u32);
+            }
+
             if let Some(initial_start) = initial_start {
-                let last_end = raw_samples.iter().map(|s| s.end).max().unwrap();
                 elapsed_picos =
-                    last_end.duration_since(initial_start, timer).picos;
+                    round.end.duration_since(initial_start, timer).picos;
             } else {
                 // Progress by at least 1ns to prevent extremely fast
                 // functions from taking forever when `min_time` is set.
-                let progress_picos = slowest_time.picos.max(1_000);
+                let progress_picos = round.slowest.picos.max(1_000);
                 elapsed_picos = elapsed_picos.saturating_add(progress_picos);
             }
         }
-
-        // Reset flag for ignoring allocations.
-        crate::alloc::IGNORE_ALLOC.set(false);
     }
 
     /// Returns a closure that takes the sample size and input counter, and then
     /// returns a newly recorded sample.
     fn sample_recorder<I, O>(
         &self,
-        gen_input: impl Fn() -> I,
+        config: BencherConfig<impl Fn() -> I, impl Fn(), impl Fn()>,
         benched: impl Fn(&UnsafeCell<MaybeUninit<I>>) -> O,
         drop_input: impl Fn(&UnsafeCell<MaybeUninit<I>>),
     ) -> impl Fn(
         usize,
         Option<&Barrier>,
         &mut dyn FnMut(&I),
-    ) -> ([Timestamp; 2], ThreadAllocInfo) {
+    ) -> ([Timestamp; 2], ThreadAllocInfo, Vec<(String, u64)>) {
         // We defer:
         // - Usage of `gen_input` values.
         // - Drop destructor for `O`, preventing it from affecting sample
         //   measurements. Outputs are stored into a pre-allocated buffer during
         //   the sample loop. The allocation is reused between samples to reduce
         //   time spent between samples.
+        //
+        // Per-sample setup and teardown run outside of the sample's timing and
+        // allocation tracking.
 
         let timer_kind = self.shared_context.timer.kind();
+        let BencherConfig { gen_input, sample_setup, sample_teardown } = config;
 
         move |sample_size: usize,
               barrier: Option<&Barrier>,
               count_input: &mut dyn FnMut(&I)| {
+            sample_setup();
+
             let mut defer_store = DeferStore::<I, O>::default();
 
+            // Net allocations kept by this thread since the start of the
+            // sample, including generating inputs and dropping outputs.
+            //
+            // Live allocations are reset when the timed section starts, so the
+            // net change before then is kept separately.
+            let live_allocs = || -> AllocTally<ThreadAllocCountSigned> {
+                ThreadAllocInfo::current()
+                    .map(|info| {
+                        // SAFETY: We have exclusive access.
+                        let info = unsafe { info.as_ref() };
+                        AllocTally {
+                            count: info.current_count,
+                            size: info.current_size,
+                        }
+                    })
+                    .unwrap_or_default()
+            };
+            let live_allocs_at_start = live_allocs();
+            let retained_before_timing = Cell::new(AllocTally::default());
+
+            // Allocations by this thread are tallied by its `ThreadAllocInfo`
+            // rather than shared tallies for other threads.
+            crate::alloc::global::set_sampling(true);
+
             let mut saved_alloc_info = ThreadAllocInfo::new();
-            let mut save_alloc_info = || {
+            let mut save_alloc_info = |others: Option<ThreadAllocTallyMap>| {
                 if crate::alloc::IGNORE_ALLOC.get() {
                     return;
                 }
@@ -911,6 +1723,10 @@ impl<'a> BenchContext<'a> {
                     // SAFETY: We have exclusive access.
                     saved_alloc_info = unsafe { alloc_info.as_ptr().read() };
                 }
+
+                if let Some(tallies) = others {
+                    saved_alloc_info.tallies.add(&tallies);
+                }
             };
 
             // Synchronize all threads to start timed section simultaneously and
@@ -918,12 +1734,28 @@ impl<'a> BenchContext<'a> {
             //
             // This ensures work external to the timed section does not affect
             // the timing of other threads.
+            //
+            // At the end, a single thread returns allocation tallies from
+            // threads not recording samples.
             let sync_threads = |is_start: bool| {
-                sync_impl(barrier, is_start);
+                if is_start {
+                    let live = live_allocs();
+                    retained_before_timing.set(AllocTally {
+                        count: live.count - live_allocs_at_start.count,
+                        size: live.size - live_allocs_at_start.size,
+                    });
+                }
 
                 // Monomorphize implementation to reduce code size.
                 #[inline(never)]
-                fn sync_impl(barrier: Option<&Barrier>, is_start: bool) {
+                fn sync_impl(
+                    barrier: Option<&Barrier>,
+                    is_start: bool,
+                ) -> Option<ThreadAllocTallyMap> {
+                    if !is_start {
+                        crate::alloc::call_site::set_active(false);
+                    }
+
                     // Ensure benchmarked section has a `ThreadAllocInfo`
                     // allocated for the current thread and clear previous info.
                     let alloc_info = if is_start {
@@ -932,11 +1764,33 @@ impl<'a> BenchContext<'a> {
                         None
                     };
 
+                    // Start tallying threads not recording samples before any
+                    // thread can pass the barrier and begin its timed section.
+                    // The main thread does this since it is always present.
+                    //
+                    // Threads spawned by the benchmark are only tallied if
+                    // profiling is enabled for its threads.
+                    if is_start
+                        && roles::thread_index() == 0
+                        && crate::alloc::is_profiling()
+                    {
+                        crate::alloc::global::start();
+                    }
+
                     // Synchronize all threads.
                     //
                     // This is the final synchronization point for the end.
-                    if let Some(barrier) = barrier {
-                        barrier.wait();
+                    let is_leader = match barrier {
+                        Some(barrier) => barrier.wait(),
+                        None => true,
+                    };
+
+                    if !is_start {
+                        return if is_leader && crate::alloc::is_profiling() {
+                            Some(crate::alloc::global::stop())
+                        } else {
+                            None
+                        };
                     }
 
                     if let Some(mut alloc_info) = alloc_info {
@@ -950,7 +1804,12 @@ impl<'a> BenchContext<'a> {
                             barrier.wait();
                         }
                     }
+
+                    crate::alloc::call_site::set_active(true);
+                    None
                 }
+
+                sync_impl(barrier, is_start)
             };
 
             // The following logic chooses how to efficiently sample the
@@ -994,8 +1853,8 @@ impl<'a> BenchContext<'a> {
                 }
 
                 sample_end = UntaggedTimestamp::end(timer_kind);
-                sync_threads(false);
-                save_alloc_info();
+                let other_threads = sync_threads(false);
+                save_alloc_info(other_threads);
 
                 // Drop outputs and inputs.
                 for _ in 0..sample_size {
@@ -1054,8 +1913,8 @@ impl<'a> BenchContext<'a> {
                         }
 
                         sample_end = UntaggedTimestamp::end(timer_kind);
-                        sync_threads(false);
-                        save_alloc_info();
+                        let other_threads = sync_threads(false);
+                        save_alloc_info(other_threads);
 
                         // Prevent the optimizer from removing writes to inputs
                         // and outputs in the sample loop.
@@ -1103,8 +1962,8 @@ impl<'a> BenchContext<'a> {
                         }
 
                         sample_end = UntaggedTimestamp::end(timer_kind);
-                        sync_threads(false);
-                        save_alloc_info();
+                        let other_threads = sync_threads(false);
+                        save_alloc_info(other_threads);
 
                         // Prevent the optimizer from removing writes to inputs
                         // in the sample loop.
@@ -1130,7 +1989,25 @@ impl<'a> BenchContext<'a> {
                 ]
             };
 
-            (interval, saved_alloc_info)
+            // Record live allocations after all inputs and outputs are dropped,
+            // relative to the start of the sample.
+            drop(defer_store);
+            if !crate::alloc::IGNORE_ALLOC.get() {
+                let live = live_allocs();
+                let before = retained_before_timing.get();
+
+                saved_alloc_info.current_count = before.count + live.count;
+                saved_alloc_info.current_size = before.size + live.size;
+            }
+
+            crate::alloc::global::set_sampling(false);
+
+            // Resolve call sites captured during the timed section.
+            let call_sites = crate::alloc::call_site::take();
+
+            sample_teardown();
+
+            (interval, saved_alloc_info, call_sites)
         }
     }
 
@@ -1146,15 +2023,84 @@ impl<'a> BenchContext<'a> {
     }
 
     pub fn compute_stats(&self) -> Stats {
-        let time_samples = &self.samples.time_samples;
-        let alloc_info_by_sample = &self.samples.alloc_info_by_sample;
+        self.compute_stats_for(&self.samples, &self.counters)
+    }
+
+    /// Computes statistics over samples recorded by threads matching `keep`.
+    fn compute_thread_subset_stats(
+        &self,
+        keep: impl Fn(usize) -> bool,
+    ) -> Stats {
+        let thread_count = self.thread_count.get();
+        let keep_sample = |index: usize| keep(index % thread_count);
+
+        let samples = self.samples.filter_threads(thread_count, &keep);
+        let counters = self.counters.filter_input_counts(keep_sample);
+
+        self.compute_stats_for(&samples, &counters)
+    }
+
+    /// Computes statistics for each thread of a multi-threaded benchmark.
+    pub fn compute_thread_stats(&self) -> Vec<BenchStats> {
+        let thread_count = self.thread_count.get();
+        if thread_count == 1 {
+            return Vec::new();
+        }
+
+        (0..thread_count)
+            .map(|thread| BenchStats {
+                stats: self.compute_thread_subset_stats(|t| t == thread),
+                roles: Vec::new(),
+                threads: Vec::new(),
+            })
+            .collect()
+    }
+
+    /// Computes statistics for the threads of each role, if benchmarked with
+    /// `Bencher::bench_roles`.
+    pub fn compute_role_stats(&self) -> Vec<RoleStats> {
+        let Some(roles) = &self.thread_roles else {
+            return Vec::new();
+        };
+
+        (0..roles.names.len())
+            .filter_map(|role| {
+                let role_thread_count = roles.thread_count(role);
+                if role_thread_count == 0 {
+                    return None;
+                }
+
+                let stats = self.compute_thread_subset_stats(|t| {
+                    roles.by_thread[t] == role
+                });
+
+                Some(RoleStats {
+                    name: roles.names[role].to_owned(),
+                    thread_count: role_thread_count,
+                    stats: BenchStats {
+                        stats,
+                        roles: Vec::new(),
+                        threads: Vec::new(),
+                    },
+                })
+            })
+            .collect()
+    }
+
+    fn compute_stats_for(
+        &self,
+        samples: &SampleCollection,
+        counters: &CounterCollection,
+    ) -> Stats {
+        let time_samples = &samples.time_samples;
+        let alloc_info_by_sample = &samples.alloc_info_by_sample;
 
         let sample_count = time_samples.len();
-        let sample_size = self.samples.sample_size;
+        let sample_size = samples.sample_size;
 
-        let total_count = self.samples.iter_count();
+        let total_count = samples.iter_count();
 
-        let total_duration = self.samples.total_duration();
+        let total_duration = samples.total_duration();
         let mean_duration = FineDuration {
             picos: total_duration
                 .picos
@@ -1163,19 +2109,19 @@ impl<'a> BenchContext<'a> {
         };
 
         // Samples sorted by duration.
-        let sorted_samples = self.samples.sorted_samples();
+        let sorted_samples = samples.sorted_samples();
         let median_samples = util::slice_middle(&sorted_samples);
 
         let index_of_sample = |sample: &TimeSample| -> usize {
-            util::slice_ptr_index(&self.samples.time_samples, sample)
+            util::slice_ptr_index(&samples.time_samples, sample)
         };
 
         let counter_count_for_sample = |sample: &TimeSample,
                                         counter_kind: KnownCounterKind|
          -> Option<MaxCountUInt> {
-            let counts = self.counters.counts(counter_kind);
+            let counts = counters.counts(counter_kind);
 
-            let index = if self.counters.uses_input_counts(counter_kind) {
+            let index = if counters.uses_input_counts(counter_kind) {
                 index_of_sample(sample)
             } else {
                 0
@@ -1225,7 +2171,7 @@ impl<'a> BenchContext<'a> {
                     .last()
                     .and_then(|s| counter_count_for_sample(s, counter_kind))?,
                 median,
-                mean: self.counters.mean_count(counter_kind),
+                mean: counters.mean_count(counter_kind),
             })
         });
 
@@ -1235,9 +2181,7 @@ impl<'a> BenchContext<'a> {
                     .and_then(|sample| {
                         u32::try_from(index_of_sample(sample)).ok()
                     })
-                    .and_then(|index| {
-                        self.samples.alloc_info_by_sample.get(&index)
-                    })
+                    .and_then(|index| samples.alloc_info_by_sample.get(&index))
             };
 
         let sample_alloc_tally =
@@ -1250,18 +2194,73 @@ impl<'a> BenchContext<'a> {
 
         let mut alloc_total_max_count = 0u128;
         let mut alloc_total_max_size = 0u128;
+        let mut alloc_total_retained_count = 0i128;
+        let mut alloc_total_retained_size = 0i128;
         let mut alloc_total_tallies = TotalAllocTallyMap::default();
+        let mut alloc_total_sizes = AllocSizeBins::<u128>::default();
 
         for alloc_info in alloc_info_by_sample.values() {
             alloc_total_max_count += alloc_info.max_count as u128;
             alloc_total_max_size += alloc_info.max_size as u128;
+            alloc_total_retained_count += alloc_info.current_count as i128;
+            alloc_total_retained_size += alloc_info.current_size as i128;
             alloc_info.tallies.add_to_total(&mut alloc_total_tallies);
+
+            for (total, &count) in alloc_total_sizes
+                .values
+                .iter_mut()
+                .zip(&alloc_info.size_bins.values)
+            {
+                *total += count as u128;
+            }
         }
 
         let sample_size = f64::from(sample_size);
+
+        // Distribution of a per-iteration allocation statistic over all
+        // samples, including samples that did not allocate.
+        let alloc_distribution =
+            |get: &dyn Fn(&ThreadAllocInfo) -> [f64; 2]| {
+                let mut counts = Vec::with_capacity(sample_count);
+                let mut sizes = Vec::with_capacity(sample_count);
+
+                for index in 0..sample_count as u32 {
+                    let [count, size] = alloc_info_by_sample
+                        .get(&index)
+                        .map(get)
+                        .unwrap_or_default();
+
+                    counts.push(count / sample_size);
+                    sizes.push(size / sample_size);
+                }
+
+                AllocTally {
+                    count: Distribution::from_values(&mut counts),
+                    size: Distribution::from_values(&mut sizes),
+                }
+            };
+
+        // Net allocations kept at the end of a sample, per iteration.
+        let sample_retained = |sample: Option<&TimeSample>| -> AllocTally<f64> {
+            let alloc_info = sample_alloc_info(sample);
+
+            AllocTally {
+                count: alloc_info
+                    .map(|info| info.current_count as f64)
+                    .unwrap_or_default()
+                    / sample_size,
+                size: alloc_info
+                    .map(|info| info.current_size as f64)
+                    .unwrap_or_default()
+                    / sample_size,
+            }
+        };
+
         Stats {
             sample_count: sample_count as u32,
+            sample_size: samples.sample_size,
             iter_count: total_count,
+            sample_durations: time_samples.iter().map(|s| s.duration).collect(),
             time: StatsSet {
                 fastest: min_duration,
                 slowest: max_duration,
@@ -1299,8 +2298,6 @@ impl<'a> BenchContext<'a> {
                             / sample_size,
                     }
                 },
-                // TODO: Switch to median of alloc info itself, rather than
-                // basing off of median times.
                 median: {
                     let alloc_info_for_median = |index| {
                         sample_alloc_info(median_samples.get(index).copied())
@@ -1336,6 +2333,28 @@ impl<'a> BenchContext<'a> {
                 },
             }
             .transpose(),
+            retained: StatsSet {
+                fastest: sample_retained(sorted_samples.first().copied()),
+                slowest: sample_retained(sorted_samples.last().copied()),
+                median: {
+                    let median_count = median_samples.len().max(1) as f64;
+
+                    let [a, b] = [0, 1].map(|index| {
+                        sample_retained(median_samples.get(index).copied())
+                    });
+
+                    AllocTally {
+                        count: (a.count + b.count) / median_count,
+                        size: (a.size + b.size) / median_count,
+                    }
+                },
+                mean: AllocTally {
+                    count: alloc_total_retained_count as f64
+                        / total_count as f64,
+                    size: alloc_total_retained_size as f64 / total_count as f64,
+                },
+            }
+            .transpose(),
             alloc_tallies: AllocOpMap {
                 values: AllocOp::ALL
                     .map(|op| StatsSet {
@@ -1396,6 +2415,46 @@ impl<'a> BenchContext<'a> {
                     })
                     .map(StatsSet::transpose),
             },
+            max_alloc_distribution: alloc_distribution(&|info| {
+                [info.max_count as f64, info.max_size as f64]
+            }),
+            retained_distribution: alloc_distribution(&|info| {
+                [info.current_count as f64, info.current_size as f64]
+            }),
+            alloc_distributions: AllocOpMap {
+                values: AllocOp::ALL.map(|op| {
+                    alloc_distribution(&|info| {
+                        let tally = info.tallies.get(op);
+                        [tally.count as f64, tally.size as f64]
+                    })
+                }),
+            },
+            alloc_call_sites: {
+                let mut call_sites: Vec<(String, f64)> = samples
+                    .alloc_call_sites
+                    .iter()
+                    .map(|(call_site, &count)| {
+                        (call_site.clone(), count as f64 / total_count as f64)
+                    })
+                    .collect();
+
+                call_sites.sort_unstable_by(
+                    |(a_site, a_count), (b_site, b_count)| {
+                        b_count
+                            .total_cmp(a_count)
+                            .then_with(|| a_site.cmp(b_site))
+                    },
+                );
+                call_sites.truncate(crate::alloc::call_site::MAX_CALL_SITES);
+                call_sites
+            },
+            alloc_sizes: AllocSizeBins {
+                values: alloc_total_sizes
+                    .values
+                    .map(|count| count as f64 / total_count as f64),
+            },
+            peak_rss: None,
+            rss_delta: None,
             counts,
         }
     }
@@ -1403,7 +2462,7 @@ impl<'a> BenchContext<'a> {
 
 impl<T> StatsSet<AllocTally<T>> {
     #[inline]
-    pub fn transpose(self) -> AllocTally<StatsSet<T>> {
+    pub(crate) fn transpose(self) -> AllocTally<StatsSet<T>> {
         AllocTally {
             count: StatsSet {
                 fastest: self.fastest.count,
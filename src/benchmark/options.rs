@@ -1,13 +1,13 @@
 use std::{borrow::Cow, time::Duration};
 
-use crate::{counter::CounterSet, time::FineDuration};
+use crate::{benchmark::CpuPin, counter::CounterSet, time::FineDuration};
 
 /// Benchmarking options set directly by the user in `#[divan::bench]` and
 /// `#[divan::bench_group]`.
 ///
 /// Changes to fields must be reflected in the "Options" sections of the docs
 /// for `#[divan::bench]` and `#[divan::bench_group]`.
-#[derive(Clone, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct BenchOptions<'a> {
     /// The number of sample recordings.
     pub sample_count: Option<u32>,
@@ -23,6 +23,9 @@ pub struct BenchOptions<'a> {
     /// cached on first retrieval.
     pub threads: Option<Cow<'a, [usize]>>,
 
+    /// The CPUs to pin benchmarking threads to.
+    pub pin: Option<CpuPin>,
+
     /// Counts the number of values processed each iteration of a benchmarked
     /// function.
     pub counters: CounterSet,
@@ -38,6 +41,18 @@ pub struct BenchOptions<'a> {
     /// [`Drop`].
     pub skip_ext_time: Option<bool>,
 
+    /// The maximum number of allocations allowed per iteration, when using
+    /// [`AllocProfiler`](crate::AllocProfiler).
+    pub max_allocs: Option<u64>,
+
+    /// The maximum number of bytes allowed to be retained per iteration, when
+    /// using [`AllocProfiler`](crate::AllocProfiler).
+    pub max_retained_bytes: Option<u64>,
+
+    /// Whether [`AllocProfiler`](crate::AllocProfiler) tallies allocations.
+    /// This is `true` by default.
+    pub alloc_profile: Option<bool>,
+
     /// Whether the benchmark should be ignored.
     ///
     /// This may be set within the attribute or with a separate
@@ -64,13 +79,39 @@ impl<'a> BenchOptions<'a> {
             min_time: self.min_time.or(other.min_time),
             max_time: self.max_time.or(other.max_time),
             skip_ext_time: self.skip_ext_time.or(other.skip_ext_time),
+            max_allocs: self.max_allocs.or(other.max_allocs),
+            max_retained_bytes: self
+                .max_retained_bytes
+                .or(other.max_retained_bytes),
+            alloc_profile: self.alloc_profile.or(other.alloc_profile),
             ignore: self.ignore.or(other.ignore),
 
             // `Clone` values:
+            pin: self.pin.as_ref().or(other.pin.as_ref()).cloned(),
             counters: self.counters.overwrite(&other.counters),
         }
     }
 
+    /// Converts borrowed values into owned values.
+    pub(crate) fn into_owned(self) -> BenchOptions<'static> {
+        BenchOptions {
+            sample_count: self.sample_count,
+            sample_size: self.sample_size,
+            threads: self
+                .threads
+                .map(|threads| Cow::Owned(threads.into_owned())),
+            pin: self.pin,
+            counters: self.counters,
+            min_time: self.min_time,
+            max_time: self.max_time,
+            skip_ext_time: self.skip_ext_time,
+            max_allocs: self.max_allocs,
+            max_retained_bytes: self.max_retained_bytes,
+            alloc_profile: self.alloc_profile,
+            ignore: self.ignore,
+        }
+    }
+
     /// Returns `true` if non-zero samples are specified.
     #[inline]
     pub(crate) fn has_samples(&self) -> bool {
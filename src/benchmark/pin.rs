@@ -0,0 +1,177 @@
+use std::{borrow::Cow, error::Error, fmt, str::FromStr};
+
+use crate::util::{self, affinity, thread::ThreadPool};
+
+/// How to pin benchmarking threads to CPUs, set by
+/// [`#[divan::bench(pin = ...)]`](macro@crate::bench#pin) or `--pin-cpus`.
+///
+/// Thread 0 is the main thread, and later threads are auxiliary threads of a
+/// multi-threaded benchmark. Threads beyond the CPUs to pin to are not pinned.
+///
+/// Pinning is only supported on Linux and has no effect elsewhere.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CpuPin {
+    /// Pins each thread to the CPU at its index.
+    Cpus(Cow<'static, [usize]>),
+
+    /// Pins threads to separate physical cores, before using other hardware
+    /// threads of those cores.
+    ///
+    /// This measures threads that do not compete for execution resources.
+    ///
+    /// This is `physical-cores` on the command line.
+    PhysicalCores,
+
+    /// Pins threads to all hardware threads of a physical core, before moving
+    /// on to the next core.
+    ///
+    /// This measures threads that share caches of the same core.
+    ///
+    /// This is `core-siblings` on the command line.
+    CoreSiblings,
+}
+
+impl CpuPin {
+    /// Returns the CPU of each pinned thread for a benchmark on
+    /// `thread_count` threads.
+    ///
+    /// Returns nothing if pinning is not supported.
+    pub(crate) fn cpus(&self, thread_count: usize) -> Vec<usize> {
+        let Some(allowed) = affinity::current() else {
+            return Vec::new();
+        };
+
+        let mut cpus = match self {
+            Self::Cpus(cpus) => {
+                let cpus = &cpus[..cpus.len().min(thread_count)];
+
+                // Only record pinning that can be applied.
+                match cpus.iter().position(|cpu| !allowed.contains(cpu)) {
+                    Some(thread) => {
+                        eprintln!(
+                            "warning: CPU {} is not available; not pinning thread {thread} or later threads",
+                            cpus[thread],
+                        );
+                        cpus[..thread].to_vec()
+                    }
+                    None => cpus.to_vec(),
+                }
+            }
+
+            Self::PhysicalCores => {
+                let cores = affinity::physical_cores(&allowed);
+                let max_siblings = cores.iter().map(Vec::len).max();
+
+                (0..max_siblings.unwrap_or(0))
+                    .flat_map(|i| {
+                        cores.iter().filter_map(move |core| core.get(i))
+                    })
+                    .copied()
+                    .collect()
+            }
+
+            Self::CoreSiblings => affinity::physical_cores(&allowed).concat(),
+        };
+
+        cpus.truncate(thread_count);
+        cpus
+    }
+}
+
+impl FromStr for CpuPin {
+    type Err = Box<dyn Error + Send + Sync>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "physical-cores" => Ok(Self::PhysicalCores),
+            "core-siblings" => Ok(Self::CoreSiblings),
+            _ => match affinity::parse_cpu_list(s) {
+                Some(cpus) => Ok(Self::Cpus(Cow::Owned(cpus))),
+                None => Err(format!(
+                    "expected 'physical-cores', 'core-siblings', or a list of \
+                     CPUs such as '0,2,4-7', found '{s}'"
+                )
+                .into()),
+            },
+        }
+    }
+}
+
+impl fmt::Display for CpuPin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cpus(cpus) => {
+                for (i, cpu) in cpus.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{cpu}")?;
+                }
+                Ok(())
+            }
+            Self::PhysicalCores => f.write_str("physical-cores"),
+            Self::CoreSiblings => f.write_str("core-siblings"),
+        }
+    }
+}
+
+/// Pins each thread of a benchmark on `thread_count` threads to its CPU in
+/// `cpus`, and returns the CPUs that threads were pinned to.
+///
+/// If a thread cannot be pinned, it and later threads are left unpinned, so
+/// that the returned CPUs are those of the first threads.
+///
+/// The returned guard restores the original affinity of all threads, so that
+/// later benchmarks are unaffected.
+pub(crate) fn pin_threads<'a>(
+    thread_pool: &'a ThreadPool,
+    cpus: &[usize],
+    thread_count: usize,
+) -> (Vec<usize>, Option<impl Drop + 'a>) {
+    let cpus = &cpus[..cpus.len().min(thread_count)];
+    if cpus.is_empty() {
+        return (Vec::new(), None);
+    }
+
+    // Auxiliary threads are spawned by the main thread, so they have its
+    // original affinity.
+    let Some(original_cpus) = affinity::current() else {
+        return (Vec::new(), None);
+    };
+
+    let aux_thread_count = thread_count - 1;
+
+    let mut is_pinned = Vec::with_capacity(thread_count);
+    _ = thread_pool.par_extend(&mut is_pinned, aux_thread_count, |thread| {
+        let Some(&cpu) = cpus.get(thread) else {
+            return false;
+        };
+
+        let is_pinned = affinity::set_current(&[cpu]);
+        if !is_pinned {
+            eprintln!("warning: Failed to pin thread {thread} to CPU {cpu}");
+        }
+        is_pinned
+    });
+
+    let pinned_count = is_pinned[..cpus.len()]
+        .iter()
+        .take_while(|&&is_pinned| is_pinned == Some(true))
+        .count();
+
+    if pinned_count < cpus.len() {
+        _ = thread_pool.broadcast(aux_thread_count, |thread| {
+            if (pinned_count..cpus.len()).contains(&thread) {
+                affinity::set_current(&original_cpus);
+            }
+        });
+    }
+
+    let unpin_threads = util::defer(move || {
+        _ = thread_pool.broadcast(aux_thread_count, |_| {
+            affinity::set_current(&original_cpus);
+        });
+    });
+
+    (cpus[..pinned_count].to_vec(), Some(unpin_threads))
+}
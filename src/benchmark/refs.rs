@@ -0,0 +1,82 @@
+/// The output type of a function that borrows its `&'i mut I` input.
+///
+/// This allows [`Bencher::bench_refs_borrowing`](crate::Bencher::bench_refs_borrowing)
+/// and [`Bencher::bench_local_refs_borrowing`](crate::Bencher::bench_local_refs_borrowing)
+/// to name an output type that references the input, such as `&'i str` or
+/// `Token<'i>`, for every lifetime `'i` at once.
+///
+/// This is implemented for all functions that take `&'i mut I`, so it only
+/// needs to be named when forwarding benchmarked functions through generic
+/// code.
+///
+/// The `Bound` parameter implies `I: 'i` so that `for<'i> RefsOutput<'i, I>`
+/// does not require `I: 'static`.
+///
+/// # Examples
+///
+/// ```
+/// use divan::{Bencher, RefsOutput};
+///
+/// fn bench_parser<P>(bencher: Bencher, parse: P)
+/// where
+///     P: for<'i> RefsOutput<'i, String>
+///         + for<'i> Fn(&'i mut String) -> <P as RefsOutput<'i, String>>::Output
+///         + Sync,
+/// {
+///     bencher
+///         .with_inputs(|| String::from("hello world"))
+///         .bench_refs_borrowing(parse);
+/// }
+///
+/// fn first_word(s: &mut String) -> &str {
+///     s.split(' ').next().unwrap_or_default()
+/// }
+///
+/// #[divan::bench]
+/// fn bench(bencher: Bencher) {
+///     bench_parser(bencher, first_word);
+/// }
+/// ```
+pub trait RefsOutput<'i, I, Bound = &'i mut I> {
+    /// The type returned when given `&'i mut I`.
+    type Output;
+}
+
+impl<'i, I, F, O> RefsOutput<'i, I> for F
+where
+    F: FnMut(&'i mut I) -> O,
+{
+    type Output = O;
+}
+
+/// Makes a closure's output borrow from its `&mut I` input, so that it can be
+/// passed to [`Bencher::bench_refs_borrowing`](crate::Bencher::bench_refs_borrowing)
+/// or [`Bencher::bench_local_refs_borrowing`](crate::Bencher::bench_local_refs_borrowing).
+///
+/// Closures cannot otherwise infer that their returned reference borrows from
+/// their input. This is not needed for function items, which can also return
+/// types other than references, such as `Token<'i>`.
+///
+/// # Examples
+///
+/// ```
+/// #[divan::bench]
+/// fn bench(bencher: divan::Bencher) {
+///     let separator = ' ';
+///
+///     bencher
+///         .with_inputs(|| String::from("hello world"))
+///         .bench_refs_borrowing(divan::borrowing(|s: &mut String| {
+///             s.make_ascii_uppercase();
+///             s.split(separator).next().unwrap_or_default()
+///         }));
+/// }
+/// ```
+#[inline]
+pub fn borrowing<I, O, B>(benched: B) -> B
+where
+    O: ?Sized,
+    B: for<'i> Fn(&'i mut I) -> &'i O,
+{
+    benched
+}
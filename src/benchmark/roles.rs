@@ -0,0 +1,110 @@
+use std::cell::Cell;
+
+/// A named group of threads for
+/// [`Bencher::bench_roles`](crate::Bencher::bench_roles).
+///
+/// Threads are assigned to roles in the order they are given, starting with
+/// the main thread. Roles created with [`ThreadRole::new`] take a fixed number
+/// of threads, and roles created with [`ThreadRole::rest`] split the threads
+/// left over.
+#[derive(Clone, Copy, Debug)]
+pub struct ThreadRole {
+    name: &'static str,
+    threads: Option<usize>,
+}
+
+impl ThreadRole {
+    /// Creates a role run by a fixed number of threads.
+    ///
+    /// If the benchmark runs on fewer threads than all roles need, later roles
+    /// get fewer threads or are not run at all.
+    #[inline]
+    pub const fn new(name: &'static str, threads: usize) -> Self {
+        Self { name, threads: Some(threads) }
+    }
+
+    /// Creates a role run by the threads left over by
+    /// [fixed-size](Self::new) roles.
+    ///
+    /// If there are multiple such roles, leftover threads are split evenly
+    /// between them, with earlier roles getting any remainder.
+    #[inline]
+    pub const fn rest(name: &'static str) -> Self {
+        Self { name, threads: None }
+    }
+
+    /// The name displayed for this role.
+    #[inline]
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+/// The roles of each thread running a benchmark.
+pub(crate) struct RoleAssignment {
+    /// Names of roles, in the order they were given.
+    pub names: Vec<&'static str>,
+
+    /// The index into `names` for each thread, where thread 0 is the main
+    /// thread.
+    pub by_thread: Vec<usize>,
+}
+
+impl RoleAssignment {
+    pub fn new(roles: &[ThreadRole], thread_count: usize) -> Self {
+        assert!(!roles.is_empty(), "benchmark must have at least one role");
+
+        let fixed_threads: usize =
+            roles.iter().filter_map(|role| role.threads).sum();
+        let rest_roles =
+            roles.iter().filter(|role| role.threads.is_none()).count();
+
+        let leftover = thread_count.saturating_sub(fixed_threads);
+        assert!(
+            leftover == 0 || rest_roles > 0,
+            "benchmark runs on {thread_count} threads but its roles only use \
+             {fixed_threads}; use `ThreadRole::rest` for a role that takes the \
+             remaining threads",
+        );
+
+        let mut by_thread = Vec::with_capacity(thread_count);
+        let mut rest_index = 0;
+
+        for (index, role) in roles.iter().enumerate() {
+            let threads = role.threads.unwrap_or_else(|| {
+                let threads = leftover / rest_roles
+                    + usize::from(rest_index < leftover % rest_roles);
+                rest_index += 1;
+                threads
+            });
+
+            let threads = threads.min(thread_count - by_thread.len());
+            by_thread.extend(std::iter::repeat(index).take(threads));
+        }
+
+        Self { names: roles.iter().map(ThreadRole::name).collect(), by_thread }
+    }
+
+    /// Returns the number of threads assigned to `role`.
+    pub fn thread_count(&self, role: usize) -> usize {
+        self.by_thread.iter().filter(|&&r| r == role).count()
+    }
+}
+
+thread_local! {
+    /// The index of the current thread within the running benchmark.
+    static THREAD_INDEX: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Sets the index of the current thread within the running benchmark.
+#[inline]
+pub(crate) fn set_thread_index(index: usize) {
+    THREAD_INDEX.set(index);
+}
+
+/// Returns the index of the current thread within the running benchmark,
+/// where 0 is the main thread.
+#[inline]
+pub(crate) fn thread_index() -> usize {
+    THREAD_INDEX.get()
+}
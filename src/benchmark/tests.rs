@@ -32,19 +32,52 @@ const THREAD_COUNTS: &[usize] = if cfg!(miri) {
     &[1, 2, 3, 4, 5, 6, 9]
 };
 
-#[track_caller]
-fn test_bencher(test: &mut dyn FnMut(Bencher)) {
-    // Silence Miri about leaking threads.
-    let bench_options = BenchOptions {
+/// Options for recording `SAMPLE_COUNT` samples of `SAMPLE_SIZE` iterations.
+fn test_options() -> BenchOptions<'static> {
+    BenchOptions {
         sample_count: Some(SAMPLE_COUNT),
         sample_size: Some(SAMPLE_SIZE),
         ..BenchOptions::default()
+    }
+}
+
+/// Runs `test` with a context for benchmarking on `thread_count` threads with
+/// the OS timer.
+fn with_bench_context<R>(
+    action: Action,
+    bench_options: &BenchOptions,
+    thread_count: usize,
+    test: impl FnOnce(&mut BenchContext) -> R,
+) -> R {
+    let shared_context = SharedContext {
+        action,
+        timer: Timer::Os,
+        thread_pool: ThreadPool::new(),
+        check_leaks: false,
     };
 
+    let mut bench_context = BenchContext::new(
+        &shared_context,
+        bench_options,
+        NonZeroUsize::new(thread_count).unwrap(),
+    );
+
+    test(&mut bench_context)
+}
+
+#[track_caller]
+fn test_bencher(test: &mut dyn FnMut(Bencher)) {
+    // Silence Miri about leaking threads.
+    let bench_options = test_options();
+
     for timer in Timer::available() {
         for action in [Action::Bench, Action::Test] {
-            let shared_context =
-                SharedContext { action, timer, thread_pool: ThreadPool::new() };
+            let shared_context = SharedContext {
+                action,
+                timer,
+                thread_pool: ThreadPool::new(),
+                check_leaks: false,
+            };
 
             for &thread_count in THREAD_COUNTS {
                 let mut bench_context = BenchContext::new(
@@ -571,3 +604,397 @@ mod zst_input {
         assert_eq!(ZST_COUNT.load(SeqCst), 0);
     }
 }
+
+/// Tests outputs that borrow from inputs. When run under Miri, this catches
+/// inputs being dropped before their outputs.
+mod borrowed_output {
+    use super::*;
+
+    /// Reads the borrowed input when dropped.
+    struct Token<'a>(&'a mut String);
+
+    impl Drop for Token<'_> {
+        fn drop(&mut self) {
+            assert_eq!(self.0.len(), 26);
+            self.0.make_ascii_lowercase();
+        }
+    }
+
+    fn tokenize(s: &mut String) -> Token<'_> {
+        s.make_ascii_uppercase();
+        Token(s)
+    }
+
+    fn as_str(s: &mut String) -> &str {
+        s
+    }
+
+    #[test]
+    fn bench_refs_borrowing() {
+        test_bencher(&mut |b| {
+            b.with_inputs(make_string).bench_refs_borrowing(tokenize)
+        });
+        test_bencher(&mut |b| {
+            b.with_inputs(make_string).bench_refs_borrowing(as_str)
+        });
+        test_bencher(&mut |b| {
+            b.with_inputs(make_string)
+                .bench_refs_borrowing(borrowing(|s: &mut String| &s[1..]))
+        });
+    }
+
+    #[test]
+    fn bench_local_refs_borrowing() {
+        test_bencher(&mut |b| {
+            b.with_inputs(make_string).bench_local_refs_borrowing(tokenize)
+        });
+        test_bencher(&mut |b| {
+            b.with_inputs(make_string).bench_local_refs_borrowing(as_str)
+        });
+        test_bencher(&mut |b| {
+            b.with_inputs(make_string)
+                .bench_local_refs_borrowing(borrowing(|s: &mut String| &s[1..]))
+        });
+    }
+}
+
+mod custom {
+    use std::panic::{self, AssertUnwindSafe};
+
+    use super::*;
+
+    #[test]
+    fn run_count() {
+        for action in [Action::Bench, Action::Test] {
+            with_bench_context(action, &test_options(), 1, |bench_context| {
+                let mut total_iters = 0;
+                Bencher::new(bench_context).bench_custom(|iters| {
+                    total_iters += iters;
+                    Duration::from_nanos(10 * iters as u64)
+                });
+
+                assert!(bench_context.did_run);
+
+                if action.is_test() {
+                    assert_eq!(total_iters, 1);
+                    return;
+                }
+
+                assert_eq!(total_iters, SAMPLE_COUNT * SAMPLE_SIZE);
+
+                let stats = bench_context.compute_stats();
+                assert_eq!(stats.sample_count, SAMPLE_COUNT);
+                assert_eq!(
+                    stats.iter_count,
+                    (SAMPLE_COUNT * SAMPLE_SIZE) as u64
+                );
+                assert_eq!(stats.time.median, FineDuration { picos: 10_000 });
+                assert_eq!(stats.time.mean, FineDuration { picos: 10_000 });
+            });
+        }
+    }
+
+    #[test]
+    fn tune() {
+        let bench_options =
+            BenchOptions { sample_size: None, ..test_options() };
+
+        let stats = with_bench_context(
+            Action::Bench,
+            &bench_options,
+            1,
+            |bench_context| {
+                Bencher::new(bench_context)
+                    .bench_custom(|iters| Duration::from_micros(iters as u64));
+                bench_context.compute_stats()
+            },
+        );
+
+        // Tuning samples are discarded, so only the collected samples remain.
+        assert_eq!(stats.sample_count, SAMPLE_COUNT);
+        assert_eq!(stats.time.mean, FineDuration { picos: 1_000_000 });
+    }
+
+    /// Tests that tuning ends when times do not grow with iterations.
+    #[test]
+    fn tune_constant() {
+        let bench_options =
+            BenchOptions { sample_size: None, ..test_options() };
+
+        for duration in [Duration::ZERO, Duration::from_nanos(10)] {
+            let stats = with_bench_context(
+                Action::Bench,
+                &bench_options,
+                1,
+                |bench_context| {
+                    Bencher::new(bench_context).bench_custom(|_| duration);
+                    bench_context.compute_stats()
+                },
+            );
+
+            assert_eq!(stats.sample_count, SAMPLE_COUNT);
+            assert_ne!(stats.iter_count, 0);
+        }
+    }
+
+    /// Tests that thread counts from runtime options are ignored.
+    #[test]
+    fn runtime_threads() {
+        with_bench_context(
+            Action::Bench,
+            &test_options(),
+            2,
+            |bench_context| {
+                Bencher::new(bench_context)
+                    .bench_custom(|iters| Duration::from_nanos(iters as u64));
+
+                assert!(bench_context.did_run);
+                assert_eq!(bench_context.thread_count, NonZeroUsize::MIN);
+                assert!(!bench_context.is_thread_scalable);
+                assert_eq!(
+                    bench_context.compute_stats().sample_count,
+                    SAMPLE_COUNT
+                );
+            },
+        );
+    }
+
+    /// Tests that thread counts from the benchmark's attribute fail the
+    /// benchmark.
+    #[test]
+    fn attr_threads() {
+        with_bench_context(
+            Action::Bench,
+            &test_options(),
+            2,
+            |bench_context| {
+                bench_context.is_attr_thread_count = true;
+
+                let payload = panic::catch_unwind(AssertUnwindSafe(|| {
+                    Bencher::new(bench_context).bench_custom(|iters| {
+                        Duration::from_nanos(iters as u64)
+                    });
+                }))
+                .unwrap_err();
+
+                let failure = payload.downcast_ref::<BenchFailure>().unwrap();
+                assert!(failure
+                    .message
+                    .contains("cannot be benchmarked with `threads = 2`"));
+                assert!(!bench_context.did_run);
+            },
+        );
+    }
+}
+
+/// Tests that per-sample hooks run around every sample on the thread that runs
+/// it.
+mod sample_hooks {
+    use super::*;
+
+    thread_local! {
+        static IN_SAMPLE: Cell<bool> = const { Cell::new(false) };
+    }
+
+    fn setup(count: &AtomicUsize) {
+        assert!(!IN_SAMPLE.get());
+        IN_SAMPLE.set(true);
+        count.fetch_add(1, SeqCst);
+    }
+
+    fn teardown(count: &AtomicUsize) {
+        assert!(IN_SAMPLE.get());
+        IN_SAMPLE.set(false);
+        count.fetch_add(1, SeqCst);
+    }
+
+    #[test]
+    fn threaded() {
+        let setup_count = AtomicUsize::new(0);
+        let teardown_count = AtomicUsize::new(0);
+
+        test_bencher(&mut |b| {
+            b.with_sample_setup(|| setup(&setup_count))
+                .with_sample_teardown(|| teardown(&teardown_count))
+                .with_inputs(|| assert!(IN_SAMPLE.get()))
+                .bench_values(|()| assert!(IN_SAMPLE.get()))
+        });
+
+        assert_ne!(setup_count.load(SeqCst), 0);
+        assert_eq!(setup_count.load(SeqCst), teardown_count.load(SeqCst));
+    }
+
+    #[test]
+    fn local() {
+        let setup_count = AtomicUsize::new(0);
+        let teardown_count = AtomicUsize::new(0);
+        let mut sample_count = 0;
+
+        test_bencher(&mut |b| {
+            b.with_inputs(make_string)
+                .with_sample_setup(|| {
+                    setup(&setup_count);
+                    sample_count += 1;
+                })
+                .with_sample_teardown(|| teardown(&teardown_count))
+                .bench_local_refs(|s| {
+                    assert!(IN_SAMPLE.get());
+                    s.make_ascii_uppercase()
+                })
+        });
+
+        assert_eq!(setup_count.load(SeqCst), sample_count);
+        assert_eq!(setup_count.load(SeqCst), teardown_count.load(SeqCst));
+    }
+
+    #[test]
+    fn custom() {
+        let setup_count = AtomicUsize::new(0);
+        let teardown_count = AtomicUsize::new(0);
+
+        with_bench_context(
+            Action::Bench,
+            &test_options(),
+            1,
+            |bench_context| {
+                Bencher::new(bench_context)
+                    .with_sample_setup(|| setup(&setup_count))
+                    .with_sample_teardown(|| teardown(&teardown_count))
+                    .bench_custom(|iters| {
+                        assert!(IN_SAMPLE.get());
+                        Duration::from_nanos(10 * iters as u64)
+                    });
+            },
+        );
+
+        assert_eq!(setup_count.load(SeqCst), SAMPLE_COUNT as usize);
+        assert_eq!(teardown_count.load(SeqCst), SAMPLE_COUNT as usize);
+    }
+}
+
+mod thread_roles {
+    use std::{sync::Mutex, thread::ThreadId};
+
+    use super::*;
+
+    #[track_caller]
+    fn assign(roles: &[ThreadRole], thread_count: usize) -> Vec<usize> {
+        RoleAssignment::new(roles, thread_count).by_thread
+    }
+
+    #[test]
+    fn assignment() {
+        let writer = ThreadRole::new("writer", 1);
+        let reader = ThreadRole::rest("reader");
+        let logger = ThreadRole::rest("logger");
+
+        assert_eq!(assign(&[writer, reader], 1), [0]);
+        assert_eq!(assign(&[writer, reader], 4), [0, 1, 1, 1]);
+        assert_eq!(assign(&[reader, writer], 4), [0, 0, 0, 1]);
+        assert_eq!(assign(&[writer, reader, logger], 4), [0, 1, 1, 2]);
+        assert_eq!(assign(&[ThreadRole::new("a", 2), writer], 2), [0, 0]);
+    }
+
+    #[test]
+    #[should_panic = "use `ThreadRole::rest`"]
+    fn unused_threads() {
+        assign(&[ThreadRole::new("writer", 1)], 2);
+    }
+
+    #[test]
+    fn bench_roles() {
+        let roles_by_thread = Mutex::new(HashSet::<(ThreadId, usize)>::new());
+
+        let (stats, role_stats) = with_bench_context(
+            Action::Bench,
+            &test_options(),
+            4,
+            |bench_context| {
+                Bencher::new(bench_context).bench_roles(
+                    &[ThreadRole::new("writer", 1), ThreadRole::rest("reader")],
+                    |role| {
+                        roles_by_thread
+                            .lock()
+                            .unwrap()
+                            .insert((std::thread::current().id(), role));
+                    },
+                );
+
+                (
+                    bench_context.compute_stats(),
+                    bench_context.compute_role_stats(),
+                )
+            },
+        );
+
+        // Each thread has a single role, and the main thread is the writer.
+        let roles_by_thread = roles_by_thread.into_inner().unwrap();
+        assert_eq!(roles_by_thread.len(), 4);
+        assert!(roles_by_thread.contains(&(std::thread::current().id(), 0)));
+        assert_eq!(roles_by_thread.iter().filter(|(_, r)| *r == 1).count(), 3);
+
+        let roles: Vec<(&str, usize, u32)> = role_stats
+            .iter()
+            .map(|role| {
+                (role.name(), role.thread_count(), role.stats().sample_count())
+            })
+            .collect();
+
+        let writer_samples = stats.sample_count / 4;
+        assert_eq!(
+            roles,
+            [("writer", 1, writer_samples), ("reader", 3, writer_samples * 3)]
+        );
+    }
+}
+
+mod thread_state {
+    use std::thread::ThreadId;
+
+    use super::*;
+
+    #[test]
+    fn once_per_thread() {
+        test_bencher(&mut |b| {
+            let thread_count = b.context.thread_count.get();
+            let init_count = AtomicUsize::new(0);
+
+            b.with_thread_state(|thread| {
+                assert!(thread < thread_count);
+                init_count.fetch_add(1, SeqCst);
+
+                (std::thread::current().id(), 0usize)
+            })
+            .bench(|(thread_id, runs): &mut (ThreadId, usize)| {
+                // State is only used on the thread that created it.
+                assert_eq!(*thread_id, std::thread::current().id());
+                *runs += 1;
+            });
+
+            assert_eq!(init_count.load(SeqCst), thread_count);
+        });
+    }
+
+    #[test]
+    fn dropped() {
+        let drop_count = AtomicUsize::new(0);
+
+        struct State<'a>(&'a AtomicUsize);
+
+        impl Drop for State<'_> {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, SeqCst);
+            }
+        }
+
+        let mut thread_count_total = 0;
+
+        test_bencher(&mut |b| {
+            thread_count_total += b.context.thread_count.get();
+
+            b.with_thread_state(|_| State(&drop_count)).bench(|_| {});
+        });
+
+        assert_eq!(drop_count.load(SeqCst), thread_count_total);
+    }
+}
@@ -1,11 +1,15 @@
+use std::path::PathBuf;
+
 use clap::{
     builder::PossibleValue, value_parser, Arg, ArgAction, ColorChoice, Command,
     ValueEnum,
 };
 
 use crate::{
+    benchmark::CpuPin,
     config::{ParsedSeconds, SortingAttr},
     counter::MaxCountUInt,
+    report::OutputFormat,
     time::TimerKind,
     util,
 };
@@ -30,6 +34,17 @@ pub(crate) fn command() -> Command {
     // - timer
     // - sort
     // - sortr
+    // - output-format
+    // - output-file
+    // - criterion
+    // - criterion-dir
+    // - rss
+    // - alloc-profile
+    // - alloc-distribution
+    // - check-leaks
+    // - keep-going
+    // - thread-breakdown
+    // - pin-cpus
 
     // TODO: `--format <pretty|terse>`
 
@@ -177,6 +192,78 @@ pub(crate) fn command() -> Command {
                 .help("Set every benchmark to have a throughput of N cycles, displayed as Hertz")
                 .value_parser(value_parser!(MaxCountUInt)),
         )
+        .arg(
+            option("output-format")
+                .env("DIVAN_OUTPUT_FORMAT")
+                .value_name("bmf|github-action-benchmark")
+                .help("Also write results in a machine-readable format, to stdout instead of the tree if '--output-file' is not set")
+                .value_parser(value_parser!(OutputFormat)),
+        )
+        .arg(
+            option("output-file")
+                .env("DIVAN_OUTPUT_FILE")
+                .value_name("PATH")
+                .help("Write '--output-format' results to this file")
+                .value_parser(value_parser!(PathBuf))
+                .requires("output-format"),
+        )
+        .arg(
+            flag("criterion")
+                .env("DIVAN_CRITERION")
+                .value_parser(value_parser!(bool))
+                .help("Also write results in the layout used by Criterion to 'target/criterion'"),
+        )
+        .arg(
+            option("criterion-dir")
+                .env("DIVAN_CRITERION_DIR")
+                .value_name("DIR")
+                .help("Write '--criterion' results to this directory instead of 'target/criterion'")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            flag("rss")
+                .env("DIVAN_RSS")
+                .value_parser(value_parser!(bool))
+                .help("Report peak and change in process resident set size (RSS) for each benchmark"),
+        )
+        .arg(
+            option("alloc-profile")
+                .env("DIVAN_ALLOC_PROFILE")
+                .value_name("on|off")
+                .help("Set whether 'AllocProfiler' tallies allocations, overriding 'alloc_profile' options")
+                .value_parser(["on", "off"]),
+        )
+        .arg(
+            flag("alloc-distribution")
+                .env("DIVAN_ALLOC_DISTRIBUTION")
+                .value_parser(value_parser!(bool))
+                .help("Show minimum, maximum, median, and mean allocations over all samples instead of those of the fastest, slowest, and median samples"),
+        )
+        .arg(
+            flag("check-leaks")
+                .env("DIVAN_CHECK_LEAKS")
+                .value_parser(value_parser!(bool))
+                .help("When running benchmarks as tests, fail if they do not free all memory allocated through 'AllocProfiler'"),
+        )
+        .arg(
+            flag("keep-going")
+                .env("DIVAN_KEEP_GOING")
+                .value_parser(value_parser!(bool))
+                .help("Continue running other benchmarks after one panics, and fail at the end"),
+        )
+        .arg(
+            flag("thread-breakdown")
+                .env("DIVAN_THREAD_BREAKDOWN")
+                .value_parser(value_parser!(bool))
+                .help("Show statistics for each thread of multi-threaded benchmarks, and the spread between the fastest and slowest threads"),
+        )
+        .arg(
+            option("pin-cpus")
+                .env("DIVAN_PIN_CPUS")
+                .value_name("LIST")
+                .help("Pin benchmarking threads to CPUs: 'physical-cores', 'core-siblings', or a list such as '0,2,4-7' by thread index")
+                .value_parser(value_parser!(CpuPin)),
+        )
         // ignored:
         .args([ignored_flag("bench"), ignored_flag("nocapture"), ignored_flag("show-output")])
 }
@@ -209,3 +296,17 @@ impl ValueEnum for SortingAttr {
         Some(PossibleValue::new(name))
     }
 }
+
+impl ValueEnum for OutputFormat {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Bmf, Self::GithubActionBenchmark]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        let name = match self {
+            Self::Bmf => "bmf",
+            Self::GithubActionBenchmark => "github-action-benchmark",
+        };
+        Some(PossibleValue::new(name))
+    }
+}
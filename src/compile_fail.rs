@@ -34,3 +34,24 @@
 //! #[divan::bench(types = [], consts = ['a', 'b', 'c'])]
 //! fn bench<T, const C: i32>() {}
 //! ```
+//!
+//! # Borrowed Outputs
+//!
+//! Benchmarked functions passed to `bench_local_refs_borrowing` must not be
+//! able to retain references to inputs, since inputs are dropped after each
+//! sample.
+//!
+//! ```compile_fail
+//! use std::cell::RefCell;
+//!
+//! #[divan::bench]
+//! fn bench(bencher: divan::Bencher) {
+//!     let stash = RefCell::new(Vec::new());
+//!
+//!     bencher
+//!         .with_inputs(String::new)
+//!         .bench_local_refs_borrowing(|s: &mut String| stash.borrow_mut().push(s));
+//!
+//!     drop(stash);
+//! }
+//! ```
@@ -66,7 +66,7 @@ impl AnyCounter {
         &self,
         duration: FineDuration,
         bytes_format: BytesFormat,
-    ) -> DisplayThroughput {
+    ) -> DisplayThroughput<'_> {
         DisplayThroughput {
             counter: self,
             picos: duration.picos as f64,
@@ -15,6 +15,10 @@ struct KnownCounterInfo {
     /// cannot change.
     count_input:
         Option<Box</* unsafe */ dyn Fn(*const ()) -> MaxCountUInt + Sync>>,
+
+    /// Whether `counts` came from input, even if `count_input` is not kept by
+    /// copies made with `filter_input_counts`.
+    uses_input_counts: bool,
 }
 
 impl CounterCollection {
@@ -55,7 +59,7 @@ impl CounterCollection {
         &self,
         counter_kind: KnownCounterKind,
     ) -> bool {
-        self.info(counter_kind).count_input.is_some()
+        self.info(counter_kind).uses_input_counts
     }
 
     pub(crate) fn set_counter(&mut self, counter: AnyCounter) {
@@ -84,6 +88,7 @@ impl CounterCollection {
         // Ignore previously-set counts.
         info.counts.clear();
 
+        info.uses_input_counts = true;
         info.count_input = Some(Box::new(move |input: *const ()| {
             // SAFETY: Callers to `get_input_count` guarantee that the same `&I`
             // is passed.
@@ -111,10 +116,40 @@ impl CounterCollection {
         Some(unsafe { from_input(input as *const I as *const ()) })
     }
 
+    /// Returns a copy with only the input-based counts of samples matching
+    /// `keep`, for computing statistics over a subset of samples.
+    ///
+    /// The copy cannot count new inputs.
+    pub(crate) fn filter_input_counts(
+        &self,
+        keep: impl Fn(usize) -> bool,
+    ) -> Self {
+        Self {
+            info: self.info.each_ref().map(|info| {
+                let counts = if info.uses_input_counts {
+                    info.counts
+                        .iter()
+                        .enumerate()
+                        .filter(|&(index, _)| keep(index))
+                        .map(|(_, &count)| count)
+                        .collect()
+                } else {
+                    info.counts.clone()
+                };
+
+                KnownCounterInfo {
+                    counts,
+                    count_input: None,
+                    uses_input_counts: info.uses_input_counts,
+                }
+            }),
+        }
+    }
+
     /// Removes counts that came from input.
     pub(crate) fn clear_input_counts(&mut self) {
         for info in &mut self.info {
-            if info.count_input.is_some() {
+            if info.uses_input_counts {
                 info.counts.clear();
             }
         }
@@ -159,6 +194,7 @@ impl CounterSet {
             info: KnownCounterKind::ALL.map(|kind| KnownCounterInfo {
                 counts: self.get(kind).into_iter().collect(),
                 count_input: None,
+                uses_input_counts: false,
             }),
         }
     }
@@ -1,12 +1,20 @@
 #![allow(clippy::too_many_arguments)]
 
-use std::{borrow::Cow, cell::RefCell, fmt, num::NonZeroUsize, time::Duration};
+use std::{
+    borrow::Cow,
+    fmt, fs, io,
+    num::NonZeroUsize,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+    time::Duration,
+};
 
 use clap::ColorChoice;
 use regex::Regex;
 
 use crate::{
-    benchmark::BenchOptions,
+    benchmark::{BenchFailure, BenchOptions, CpuPin},
     config::{
         filter::{Filter, FilterSet},
         Action, ParsedSeconds, RunIgnored, SortingAttr,
@@ -16,9 +24,13 @@ use crate::{
         ItemsCount, MaxCountUInt, PrivBytesFormat,
     },
     entry::{AnyBenchEntry, BenchEntryRunner, EntryTree},
+    report::{
+        BenchInfo, BenchStats, GroupComplexity, GroupInfo, OutputFormat,
+        Report, ReportBuilder, Reporter, ThreadScaling,
+    },
     time::{Timer, TimerKind},
     tree_painter::{TreeColumn, TreePainter},
-    util::{self, thread::ThreadPool, IntoRegex},
+    util::{self, rss::RssTracker, thread::ThreadPool, IntoRegex},
     Bencher,
 };
 
@@ -34,6 +46,15 @@ pub struct Divan {
     filters: FilterSet,
     run_ignored: RunIgnored,
     bench_options: BenchOptions<'static>,
+    output_format: Option<OutputFormat>,
+    output_file: Option<PathBuf>,
+    criterion_dir: Option<PathBuf>,
+    track_rss: bool,
+    alloc_distribution: bool,
+    check_leaks: bool,
+    keep_going: bool,
+    thread_breakdown: bool,
+    reporters: Mutex<Vec<Box<dyn Reporter + Send>>>,
 }
 
 /// Context shared across all benchmarks.
@@ -46,6 +67,9 @@ pub(crate) struct SharedContext {
 
     /// Pre-spawned pool of threads for running benchmarks on.
     pub thread_pool: ThreadPool,
+
+    /// Whether to fail tests that do not free all allocated memory.
+    pub check_leaks: bool,
 }
 
 impl fmt::Debug for Divan {
@@ -58,21 +82,38 @@ impl Divan {
     /// Perform the configured action.
     ///
     /// By default, this will be [`Divan::run_benches`].
+    ///
+    /// # Panics
+    ///
+    /// Panics after all benchmarks are run if any benchmark returned an
+    /// error or, with [`Divan::keep_going`], panicked.
     pub fn main(&self) {
-        self.run_action(self.action);
+        self.run_action(self.action).assert_no_failures();
     }
 
     /// Benchmark registered functions.
-    pub fn run_benches(&self) {
-        self.run_action(Action::Bench);
+    ///
+    /// The returned [`Report`] contains the results of every benchmark run.
+    /// Benchmarks that returned an error are reported by
+    /// [`BenchReport::error`](crate::report::BenchReport::error) rather than
+    /// causing a panic. With [`Divan::keep_going`], benchmarks that panicked
+    /// are likewise reported by
+    /// [`BenchReport::panic_message`](crate::report::BenchReport::panic_message).
+    pub fn run_benches(&self) -> Report {
+        self.run_action(Action::Bench)
     }
 
     /// Test registered functions as if the `--test` flag was used.
     ///
     /// Unlike [`Divan::run_benches`], this runs each benchmarked function only
     /// once.
+    ///
+    /// # Panics
+    ///
+    /// Panics after all benchmarks are run if any benchmark returned an
+    /// error or, with [`Divan::keep_going`], panicked.
     pub fn test_benches(&self) {
-        self.run_action(Action::Test);
+        self.run_action(Action::Test).assert_no_failures();
     }
 
     /// Print registered functions as if the `--list` flag was used.
@@ -93,7 +134,7 @@ impl Divan {
         !self.run_ignored.should_run(ignored)
     }
 
-    pub(crate) fn run_action(&self, action: Action) {
+    pub(crate) fn run_action(&self, action: Action) -> Report {
         let mut tree: Vec<EntryTree> = if cfg!(miri) {
             // Miri does not work with our linker tricks.
             Vec::new()
@@ -127,14 +168,14 @@ impl Divan {
 
         // Quick exit without doing unnecessary work.
         if tree.is_empty() {
-            return;
+            return Report::default();
         }
 
         // When run under `cargo-nextest`, it provides `--list --format terse`.
         // We don't currently accept this action under any other circumstances.
         if action.is_list_terse() {
             self.run_tree_list(&tree, "");
-            return;
+            return Report::default();
         }
 
         // Sorting is after filtering to compare fewer elements.
@@ -160,8 +201,12 @@ impl Divan {
             eprintln!("Timer precision: {}", timer.precision());
         }
 
-        let shared_context =
-            SharedContext { action, timer, thread_pool: ThreadPool::new() };
+        let shared_context = SharedContext {
+            action,
+            timer,
+            thread_pool: ThreadPool::new(),
+            check_leaks: self.check_leaks,
+        };
 
         let column_widths = if action.is_bench() {
             TreeColumn::ALL.map(|column| {
@@ -176,12 +221,62 @@ impl Divan {
             [0; TreeColumn::COUNT]
         };
 
-        let tree_painter = RefCell::new(TreePainter::new(
+        let mut tree_painter = TreePainter::new(
             EntryTree::max_name_span(&tree, 0),
             column_widths,
-        ));
+            self.bytes_format,
+            self.alloc_distribution,
+        );
+
+        let mut report_builder = ReportBuilder::default();
+
+        let mut user_reporters =
+            self.reporters.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let mut reporters: Vec<&mut dyn Reporter> = vec![&mut report_builder];
+
+        // Formatted output to stdout replaces the tree.
+        let output_format = self.output_format.filter(|_| action.is_bench());
+
+        if output_format.is_none() || self.output_file.is_some() {
+            reporters.push(&mut tree_painter);
+        }
 
-        self.run_tree(action, &tree, &shared_context, None, &tree_painter);
+        reporters.extend(
+            user_reporters.iter_mut().map(|r| &mut **r as &mut dyn Reporter),
+        );
+
+        self.run_tree(action, &tree, "", &shared_context, None, &mut reporters);
+
+        drop(reporters);
+        let report = report_builder.finish(Some(timer));
+
+        if let Some(format) = output_format {
+            let path = self.output_file.as_deref();
+
+            if let Err(error) = write_output(&report, format, path) {
+                match path {
+                    Some(path) => panic!(
+                        "failed to write results to '{}': {error}",
+                        path.display()
+                    ),
+                    None => panic!("failed to write results: {error}"),
+                }
+            }
+        }
+
+        if let Some(dir) =
+            self.criterion_dir.as_deref().filter(|_| action.is_bench())
+        {
+            if let Err(error) = report.write_criterion_dir(dir) {
+                panic!(
+                    "failed to write results to '{}': {error}",
+                    dir.display()
+                );
+            }
+        }
+
+        report
     }
 
     /// Emits the entries in `tree` for the purpose of `--list --format terse`.
@@ -229,9 +324,10 @@ impl Divan {
         &self,
         action: Action,
         tree: &[EntryTree],
+        parent_path: &str,
         shared_context: &SharedContext,
         parent_options: Option<&BenchOptions>,
-        tree_painter: &RefCell<TreePainter>,
+        reporters: &mut [&mut dyn Reporter],
     ) {
         for (i, child) in tree.iter().enumerate() {
             let is_last = i == tree.len() - 1;
@@ -259,23 +355,28 @@ impl Divan {
                     action,
                     *entry,
                     args.as_deref(),
+                    parent_path,
                     shared_context,
                     options,
-                    tree_painter,
+                    reporters,
                     is_last,
                 ),
                 EntryTree::Parent { children, .. } => {
-                    tree_painter.borrow_mut().start_parent(name, is_last);
+                    let path = child_path(parent_path, name);
+                    let group = GroupInfo::new(name, &path, is_last);
+
+                    reporters.start_group(&group);
 
                     self.run_tree(
                         action,
                         children,
+                        &path,
                         shared_context,
                         options,
-                        tree_painter,
+                        reporters,
                     );
 
-                    tree_painter.borrow_mut().finish_parent();
+                    reporters.finish_group(&group);
                 }
             }
         }
@@ -286,14 +387,16 @@ impl Divan {
         action: Action,
         bench_entry: AnyBenchEntry,
         bench_arg_names: Option<&[&&str]>,
+        parent_path: &str,
         shared_context: &SharedContext,
         entry_options: Option<&BenchOptions>,
-        tree_painter: &RefCell<TreePainter>,
+        reporters: &mut [&mut dyn Reporter],
         is_last_entry: bool,
     ) {
         use crate::benchmark::BenchContext;
 
         let entry_display_name = bench_entry.display_name();
+        let entry_path = child_path(parent_path, entry_display_name);
 
         // User runtime options override all other options.
         let options: BenchOptions;
@@ -306,17 +409,29 @@ impl Divan {
         };
 
         if self.should_ignore(options.ignore.unwrap_or_default()) {
-            tree_painter
-                .borrow_mut()
-                .ignore_leaf(entry_display_name, is_last_entry);
+            reporters.ignore_bench(&BenchInfo {
+                name: entry_display_name,
+                path: &entry_path,
+                is_last: is_last_entry,
+                thread_count: 1,
+                pinned_cpus: &[],
+                options,
+            });
             return;
         }
 
         // Paint empty leaf when simply listing.
         if action.is_list() {
-            let mut tree_painter = tree_painter.borrow_mut();
-            tree_painter.start_leaf(entry_display_name, is_last_entry);
-            tree_painter.finish_empty_leaf();
+            let bench = BenchInfo {
+                name: entry_display_name,
+                path: &entry_path,
+                is_last: is_last_entry,
+                thread_count: 1,
+                pinned_cpus: &[],
+                options,
+            };
+            reporters.start_bench(&bench);
+            reporters.finish_bench(&bench, None);
             return;
         }
 
@@ -343,18 +458,31 @@ impl Divan {
         // Whether we should emit child branches for thread counts.
         let has_thread_branches = thread_counts.len() > 1;
 
+        // Whether thread counts come from the benchmark's attribute rather
+        // than from runtime options.
+        let is_attr_thread_count = self.bench_options.threads.is_none()
+            && entry_options.is_some_and(|options| options.threads.is_some());
+
         let run_bench =
-            |bench_display_name: &str,
+            |reporters: &mut [&mut dyn Reporter],
+             bench_display_name: &str,
+             bench_path: &str,
              is_last_bench: bool,
              with_bencher: &dyn Fn(Bencher)| {
+                let mut group = GroupInfo::new(
+                    bench_display_name,
+                    bench_path,
+                    is_last_bench,
+                );
+
+                // Stats of the only run, if not run over multiple threads.
+                let mut single_stats: Option<BenchStats> = None;
+
+                // Stats of each thread count, if run over multiple threads.
+                let mut thread_runs: Vec<(usize, BenchStats)> = Vec::new();
+
                 if has_thread_branches {
-                    tree_painter
-                        .borrow_mut()
-                        .start_parent(bench_display_name, is_last_bench);
-                } else {
-                    tree_painter
-                        .borrow_mut()
-                        .start_leaf(bench_display_name, is_last_bench);
+                    reporters.start_group(&group);
                 }
 
                 for (i, &thread_count) in thread_counts.iter().enumerate() {
@@ -364,19 +492,79 @@ impl Divan {
                         is_last_bench
                     };
 
-                    if has_thread_branches {
-                        tree_painter.borrow_mut().start_leaf(
-                            &format!("t={thread_count}"),
-                            is_last_thread_count,
-                        );
-                    }
+                    let thread_branch_name: String;
+                    let thread_branch_path: String;
+                    let (leaf_name, leaf_path) = if has_thread_branches {
+                        thread_branch_name = format!("t={thread_count}");
+                        thread_branch_path =
+                            child_path(bench_path, &thread_branch_name);
+                        (&*thread_branch_name, &*thread_branch_path)
+                    } else {
+                        (bench_display_name, bench_path)
+                    };
+
+                    let pin_cpus = match &options.pin {
+                        Some(pin) if shared_context.action.is_bench() => {
+                            pin.cpus(thread_count.get())
+                        }
+                        _ => Vec::new(),
+                    };
+
+                    // CPUs are only reported once pinning has been applied.
+                    let bench = BenchInfo {
+                        name: leaf_name,
+                        path: leaf_path,
+                        is_last: is_last_thread_count,
+                        thread_count: thread_count.get(),
+                        pinned_cpus: &[],
+                        options,
+                    };
+
+                    reporters.start_bench(&bench);
 
                     let mut bench_context = BenchContext::new(
                         shared_context,
                         options,
                         thread_count,
                     );
-                    with_bencher(Bencher::new(&mut bench_context));
+                    bench_context.is_attr_thread_count = is_attr_thread_count;
+                    bench_context.pin_cpus = &pin_cpus;
+
+                    let rss_tracker = (self.track_rss
+                        && shared_context.action.is_bench())
+                    .then(RssTracker::start);
+
+                    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                        with_bencher(Bencher::new(&mut bench_context))
+                    }));
+
+                    let rss = rss_tracker.map(RssTracker::finish);
+
+                    let bench = BenchInfo {
+                        pinned_cpus: &bench_context.pinned_cpus,
+                        ..bench
+                    };
+
+                    if let Err(payload) = result {
+                        // Skip remaining thread counts if the benchmark
+                        // returned an error, and continue the run.
+                        if let Some(failure) =
+                            payload.downcast_ref::<BenchFailure>()
+                        {
+                            reporters.bench_failed(&bench, &failure.message);
+                            break;
+                        }
+
+                        let message = util::panic_message(&*payload);
+                        reporters.bench_panicked(&bench, message);
+
+                        // Skip remaining thread counts, like for errors.
+                        if self.keep_going {
+                            break;
+                        }
+
+                        panic::resume_unwind(payload);
+                    }
 
                     if !bench_context.did_run {
                         eprintln!(
@@ -387,32 +575,72 @@ impl Divan {
                     let should_compute_stats = bench_context.did_run
                         && shared_context.action.is_bench();
 
-                    if should_compute_stats {
-                        let stats = bench_context.compute_stats();
-                        tree_painter.borrow_mut().finish_leaf(
-                            is_last_thread_count,
-                            &stats,
-                            self.bytes_format,
-                        );
-                    } else {
-                        tree_painter.borrow_mut().finish_empty_leaf();
+                    let stats = should_compute_stats.then(|| {
+                        let mut stats = bench_context.compute_stats();
+
+                        if let Some((peak_rss, rss_delta)) = rss {
+                            stats.peak_rss = peak_rss;
+                            stats.rss_delta = rss_delta;
+                        }
+
+                        BenchStats {
+                            stats,
+                            roles: bench_context.compute_role_stats(),
+                            threads: if self.thread_breakdown {
+                                bench_context.compute_thread_stats()
+                            } else {
+                                Vec::new()
+                            },
+                        }
+                    });
+
+                    reporters.finish_bench(&bench, stats.as_ref());
+
+                    if !has_thread_branches {
+                        single_stats = stats;
+                    } else if let Some(stats) = stats {
+                        if bench_context.is_thread_scalable {
+                            thread_runs.push((thread_count.get(), stats));
+                        }
                     }
                 }
 
                 if has_thread_branches {
-                    tree_painter.borrow_mut().finish_parent();
+                    let runs: Vec<(usize, &BenchStats)> = thread_runs
+                        .iter()
+                        .map(|(thread_count, stats)| (*thread_count, stats))
+                        .collect();
+
+                    let thread_scaling = ThreadScaling::compute(&runs);
+                    group.thread_scaling = &thread_scaling;
+
+                    reporters.finish_group(&group);
                 }
+
+                single_stats
             };
 
         match bench_entry.bench_runner() {
             BenchEntryRunner::Plain(bench) => {
-                run_bench(entry_display_name, is_last_entry, bench)
+                run_bench(
+                    reporters,
+                    entry_display_name,
+                    &entry_path,
+                    is_last_entry,
+                    bench,
+                );
             }
 
             BenchEntryRunner::Args(bench_runner) => {
-                tree_painter
-                    .borrow_mut()
-                    .start_parent(entry_display_name, is_last_entry);
+                let mut group = GroupInfo::new(
+                    entry_display_name,
+                    &entry_path,
+                    is_last_entry,
+                );
+
+                reporters.start_group(&group);
+
+                let mut arg_stats: Vec<(&str, BenchStats)> = Vec::new();
 
                 let bench_runner = bench_runner();
                 let orig_arg_names = bench_runner.arg_names();
@@ -422,18 +650,63 @@ impl Divan {
                     let is_last_arg = i == bench_arg_names.len() - 1;
                     let arg_index =
                         util::slice_ptr_index(orig_arg_names, arg_name);
+                    let arg_path = child_path(&entry_path, arg_name);
+
+                    let stats = run_bench(
+                        reporters,
+                        arg_name,
+                        &arg_path,
+                        is_last_arg,
+                        &|bencher| {
+                            bench_runner.bench(bencher, arg_index);
+                        },
+                    );
 
-                    run_bench(arg_name, is_last_arg, &|bencher| {
-                        bench_runner.bench(bencher, arg_index);
-                    });
+                    if let Some(stats) = stats {
+                        arg_stats.push((arg_name, stats));
+                    }
                 }
 
-                tree_painter.borrow_mut().finish_parent();
+                let arg_stats: Vec<(&str, &BenchStats)> = arg_stats
+                    .iter()
+                    .map(|(arg_name, stats)| (*arg_name, stats))
+                    .collect();
+                group.complexity = GroupComplexity::fit(&arg_stats);
+
+                reporters.finish_group(&group);
             }
         }
     }
 }
 
+/// Writes `report` to the file at `path`, or to stdout if `None`.
+fn write_output(
+    report: &Report,
+    format: OutputFormat,
+    path: Option<&Path>,
+) -> io::Result<()> {
+    let Some(path) = path else {
+        return report.write(format, io::stdout().lock());
+    };
+
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+
+    report.write(format, io::BufWriter::new(fs::File::create(path)?))
+}
+
+/// Returns the `::`-joined path of a child node.
+fn child_path(parent_path: &str, name: &str) -> String {
+    if parent_path.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{parent_path}::{name}")
+    }
+}
+
 /// Configuration options.
 impl Divan {
     /// Creates an instance with options set by parsing CLI arguments.
@@ -555,6 +828,10 @@ impl Divan {
             self.bench_options.threads = Some(Cow::Owned(threads));
         }
 
+        if let Some(pin) = matches.get_one::<CpuPin>("pin-cpus") {
+            self.bench_options.pin = Some(pin.clone());
+        }
+
         if let Some(&ParsedSeconds(min_time)) = matches.get_one("min-time") {
             self.bench_options.min_time = Some(min_time);
         }
@@ -593,6 +870,44 @@ impl Divan {
             self.counter_mut(CyclesCount::new(count));
         }
 
+        if let Some(&format) = matches.get_one("output-format") {
+            self.output_format = Some(format);
+        }
+
+        if let Some(path) = matches.remove_one::<PathBuf>("output-file") {
+            self.output_file = Some(path);
+        }
+
+        if let Some(dir) = matches.remove_one::<PathBuf>("criterion-dir") {
+            self = self.criterion_dir(dir);
+        } else if matches.get_flag("criterion") {
+            self = self.criterion_dir(None);
+        }
+
+        if matches.get_flag("rss") {
+            self.track_rss = true;
+        }
+
+        if matches.get_flag("check-leaks") {
+            self.check_leaks = true;
+        }
+
+        if matches.get_flag("keep-going") {
+            self.keep_going = true;
+        }
+
+        if matches.get_flag("thread-breakdown") {
+            self.thread_breakdown = true;
+        }
+
+        if matches.get_flag("alloc-distribution") {
+            self.alloc_distribution = true;
+        }
+
+        if let Some(value) = matches.get_one::<String>("alloc-profile") {
+            self.bench_options.alloc_profile = Some(value == "on");
+        }
+
         self
     }
 
@@ -610,6 +925,109 @@ impl Divan {
         self
     }
 
+    /// Adds a [`Reporter`] that receives benchmark events as they happen.
+    ///
+    /// Reporters are notified in the order they were added, after the tree
+    /// printed to the terminal.
+    ///
+    /// See [`Reporter`] for an example.
+    #[must_use]
+    pub fn reporter<R>(mut self, reporter: R) -> Self
+    where
+        R: Reporter + Send + 'static,
+    {
+        self.reporters
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(Box::new(reporter));
+        self
+    }
+
+    /// Sets whether to report the peak and change in process resident set size
+    /// (RSS) across each benchmark.
+    ///
+    /// Unlike [`AllocProfiler`](crate::AllocProfiler), this includes memory not
+    /// allocated through [`GlobalAlloc`](std::alloc::GlobalAlloc), such as
+    /// `mmap` by C libraries. Because RSS is process-wide, it includes memory
+    /// used by Divan and other threads.
+    ///
+    /// On Linux, the peak is reset before each benchmark. On other Unix
+    /// platforms, only the peak of the whole process is available. Nothing is
+    /// reported on other platforms.
+    ///
+    /// This option is equivalent to the `--rss` CLI argument or `DIVAN_RSS`
+    /// environment variable.
+    #[must_use]
+    pub fn track_rss(mut self, yes: bool) -> Self {
+        self.track_rss = yes;
+        self
+    }
+
+    /// Sets whether running benchmarks as tests fails if a benchmark does not
+    /// free all of the memory it allocates.
+    ///
+    /// When [`AllocProfiler`](crate::AllocProfiler) is the global allocator,
+    /// each benchmark is run once to warm up one-time initialization, such as
+    /// lazily-initialized statics, and then once more to check that all memory
+    /// allocated by the run is freed after its inputs and outputs are dropped.
+    /// This makes benchmarks double as leak tests under
+    /// `cargo test --benches`.
+    ///
+    /// This option is equivalent to the `--check-leaks` CLI argument or
+    /// `DIVAN_CHECK_LEAKS` environment variable.
+    #[must_use]
+    pub fn check_leaks(mut self, yes: bool) -> Self {
+        self.check_leaks = yes;
+        self
+    }
+
+    /// Sets whether to continue running other benchmarks after one panics.
+    ///
+    /// Panicked benchmarks are marked as `FAILED` with their panic message,
+    /// including panics on auxiliary benchmarking threads. Once all benchmarks
+    /// are run, [`Divan::main`] prints a summary of failures and panics so that
+    /// the process exits with a non-zero status.
+    ///
+    /// This option is equivalent to the `--keep-going` CLI argument or
+    /// `DIVAN_KEEP_GOING` environment variable.
+    #[must_use]
+    pub fn keep_going(mut self, yes: bool) -> Self {
+        self.keep_going = yes;
+        self
+    }
+
+    /// Sets whether to report statistics for each thread of benchmarks run on
+    /// multiple threads.
+    ///
+    /// Each thread is shown under its `t=N` node, followed by how far apart
+    /// the mean times of the fastest and slowest threads are. A large spread
+    /// reveals unfairness, such as threads starving for a lock.
+    ///
+    /// This option is equivalent to the `--thread-breakdown` CLI argument or
+    /// `DIVAN_THREAD_BREAKDOWN` environment variable.
+    #[must_use]
+    pub fn thread_breakdown(mut self, yes: bool) -> Self {
+        self.thread_breakdown = yes;
+        self
+    }
+
+    /// Sets whether to show the distribution of allocation statistics over all
+    /// samples, rather than allocations of the fastest, slowest, and median
+    /// samples.
+    ///
+    /// The "fastest" and "slowest" columns are then headed "min" and "max",
+    /// and show the minimum and maximum of each statistic. This reveals
+    /// allocations that vary between samples, such as from bimodal behavior,
+    /// regardless of time.
+    ///
+    /// This option is equivalent to the `--alloc-distribution` CLI argument or
+    /// `DIVAN_ALLOC_DISTRIBUTION` environment variable.
+    #[must_use]
+    pub fn alloc_distribution(mut self, yes: bool) -> Self {
+        self.alloc_distribution = yes;
+        self
+    }
+
     /// Also run benchmarks marked [`#[ignore]`](https://doc.rust-lang.org/reference/attributes/testing.html#the-ignore-attribute).
     ///
     /// This option is equivalent to the `--include-ignored` CLI argument.
@@ -743,6 +1161,17 @@ impl Divan {
         self
     }
 
+    /// Pins benchmarking threads to CPUs, overriding
+    /// [`pin`](macro@crate::bench#pin) options.
+    ///
+    /// This option is equivalent to the `--pin-cpus` CLI argument or
+    /// `DIVAN_PIN_CPUS` environment variable.
+    #[inline]
+    pub fn pin_cpus(mut self, pin: CpuPin) -> Self {
+        self.bench_options.pin = Some(pin);
+        self
+    }
+
     /// Sets the time floor for benchmarking a function.
     ///
     /// This option is equivalent to the `--min-time` CLI argument.
@@ -770,6 +1199,18 @@ impl Divan {
         self.bench_options.skip_ext_time = Some(skip);
         self
     }
+
+    /// Sets whether [`AllocProfiler`](crate::AllocProfiler) tallies
+    /// allocations, overriding
+    /// [`alloc_profile`](macro@crate::bench#alloc_profile) options.
+    ///
+    /// This option is equivalent to the `--alloc-profile` CLI argument or
+    /// `DIVAN_ALLOC_PROFILE` environment variable.
+    #[inline]
+    pub fn alloc_profile(mut self, yes: bool) -> Self {
+        self.bench_options.alloc_profile = Some(yes);
+        self
+    }
 }
 
 /// Use [`Counter`s](crate::counter::Counter) to get throughput across all
@@ -816,6 +1257,48 @@ impl Divan {
         self
     }
 
+    /// Also writes benchmark results in a machine-readable format.
+    ///
+    /// Results are written to stdout in place of the tree, unless
+    /// [`Divan::output_file`] is set.
+    ///
+    /// This option is equivalent to the `--output-format` CLI argument or
+    /// `DIVAN_OUTPUT_FORMAT` environment variable.
+    #[inline]
+    pub fn output_format(mut self, format: OutputFormat) -> Self {
+        self.output_format = Some(format);
+        self
+    }
+
+    /// Sets the file that [`Divan::output_format`] results are written to.
+    ///
+    /// This option is equivalent to the `--output-file` CLI argument or
+    /// `DIVAN_OUTPUT_FILE` environment variable.
+    #[inline]
+    pub fn output_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.output_file = Some(path.into());
+        self
+    }
+
+    /// Also writes benchmark results into a directory in the layout used by
+    /// [Criterion](https://github.com/bheisler/criterion.rs), for tools like
+    /// [`critcmp`](https://github.com/BurntSushi/critcmp).
+    ///
+    /// [`None`] here means `target/criterion` in the current Cargo workspace.
+    /// See [`Report::write_criterion_dir`] for how benchmarks are mapped to
+    /// Criterion's layout.
+    ///
+    /// This option is equivalent to the `--criterion` or `--criterion-dir` CLI
+    /// arguments, or `DIVAN_CRITERION` or `DIVAN_CRITERION_DIR` environment
+    /// variables.
+    #[must_use]
+    pub fn criterion_dir(mut self, dir: impl Into<Option<PathBuf>>) -> Self {
+        self.criterion_dir = Some(
+            dir.into().unwrap_or_else(crate::report::default_criterion_dir),
+        );
+        self
+    }
+
     /// Sets the number of bytes processed.
     ///
     /// This option is equivalent to the `--chars-count` CLI argument or
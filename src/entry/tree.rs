@@ -373,7 +373,7 @@ impl<'a> EntryTree<'a> {
         }
     }
 
-    pub fn bench_options(&self) -> Option<&'a BenchOptions> {
+    pub fn bench_options(&self) -> Option<&'a BenchOptions<'a>> {
         self.meta()?.bench_options()
     }
 
@@ -33,6 +33,7 @@ mod time;
 mod tree_painter;
 
 pub mod counter;
+pub mod report;
 
 /// `use divan::prelude::*;` to import common items.
 pub mod prelude {
@@ -135,7 +136,11 @@ pub mod prelude {
 pub use std::hint::black_box;
 
 #[doc(inline)]
-pub use crate::{alloc::AllocProfiler, benchmark::Bencher, divan::Divan};
+pub use crate::{
+    alloc::AllocProfiler,
+    benchmark::{borrowing, check, Bencher, CpuPin, RefsOutput, ThreadRole},
+    divan::Divan,
+};
 
 /// Runs all registered benchmarks.
 ///
@@ -286,6 +291,54 @@ pub fn black_box_drop<T>(dummy: T) {
 /// }
 /// ```
 ///
+/// # Errors
+///
+/// Benchmarks may return [`Result`] if the error type implements
+/// [`Display`](std::fmt::Display). If an [`Err`] is returned, the benchmark
+/// stops and the error is reported in place of its results. The rest of the run
+/// continues, and [`divan::main`](crate::main) panics after all benchmarks
+/// finish.
+///
+/// This allows setup to fail with `?` instead of calling
+/// [`unwrap`](Result::unwrap):
+///
+/// ```
+/// use divan::Bencher;
+///
+/// #[divan::bench]
+/// fn parse_fixture(bencher: Bencher) -> Result<(), std::num::ParseIntError> {
+///     let n: u64 = "42".parse()?;
+///
+///     bencher.bench(|| divan::black_box(n) * 2);
+///     Ok(())
+/// }
+///
+/// #[divan::bench]
+/// fn parse() -> Result<u64, std::num::ParseIntError> {
+///     divan::black_box("42").parse()
+/// }
+/// ```
+///
+/// Closures passed to [`Bencher`] methods may return any output, so their
+/// results are checked by wrapping them in [`divan::check`](crate::check):
+///
+/// ```
+/// #[divan::bench]
+/// fn parse(bencher: divan::Bencher) {
+///     bencher.bench(|| divan::check(divan::black_box("42").parse::<u64>()));
+/// }
+/// ```
+///
+/// To instead benchmark an error path, return the [`Result`] from the closure
+/// without checking it:
+///
+/// ```
+/// #[divan::bench]
+/// fn parse_invalid(bencher: divan::Bencher) {
+///     bencher.bench(|| divan::black_box("x").parse::<u64>());
+/// }
+/// ```
+///
 /// # Options
 ///
 /// - [`name`]
@@ -296,6 +349,7 @@ pub fn black_box_drop<T>(dummy: T) {
 /// - [`sample_count`]
 /// - [`sample_size`]
 /// - [`threads`]
+/// - [`pin`]
 /// - [`counters`]
 ///     - [`bytes_count`]
 ///     - [`chars_count`]
@@ -303,6 +357,9 @@ pub fn black_box_drop<T>(dummy: T) {
 /// - [`min_time`]
 /// - [`max_time`]
 /// - [`skip_ext_time`]
+/// - [`max_allocs`]
+/// - [`max_retained_bytes`]
+/// - [`alloc_profile`]
 /// - [`ignore`]
 ///
 /// ## `name`
@@ -646,6 +703,41 @@ pub fn black_box_drop<T>(dummy: T) {
 /// }
 /// ```
 ///
+/// ## `pin`
+/// [`pin`]: #pin
+///
+/// Benchmarking threads can be pinned to specific CPUs via the [`pin`] option.
+/// This makes results of [`threads`] benchmarks more consistent by not leaving
+/// the choice of cores to the OS. The CPU of each thread is shown under the
+/// benchmark.
+///
+/// The [`pin`] option can be set to any of:
+/// - `"physical-cores"` to pin threads to separate physical cores, before
+///   using other hardware threads of those cores.
+/// - `"core-siblings"` to fill all hardware threads of a core before moving on
+///   to the next core.
+/// - [`IntoIterator`] over [`usize`] for the CPU of each thread, starting with
+///   the main thread.
+/// - [`CpuPin`] directly.
+///
+/// ```
+/// #[divan::bench(threads = [1, 2, 4], pin = "physical-cores")]
+/// fn separate_cores() {
+///     // ...
+/// }
+///
+/// #[divan::bench(threads = 2, pin = [0, 1])]
+/// fn specific_cpus() {
+///     // ...
+/// }
+/// ```
+///
+/// Threads beyond the given CPUs are not pinned. Pinning is only supported on
+/// Linux and has no effect elsewhere.
+///
+/// This may be overridden at runtime using either the `DIVAN_PIN_CPUS`
+/// environment variable or `--pin-cpus` CLI argument.
+///
 /// ## `counters`
 /// [`counters`]: #counters
 ///
@@ -825,6 +917,73 @@ pub fn black_box_drop<T>(dummy: T) {
 /// }
 /// ```
 ///
+/// ## `max_allocs`
+/// [`max_allocs`]: #max_allocs
+///
+/// Fails the benchmark if any iteration allocates more than the given number
+/// of times. Every `alloc`, `grow`, and `shrink` operation in the benchmarked
+/// function counts as an allocation.
+///
+/// Allocations are only counted when [`AllocProfiler`] is the
+/// [global allocator](macro@global_allocator). The limit is also checked when
+/// running benchmarks as tests with `cargo test`, which makes it useful for
+/// ensuring code paths stay allocation-free:
+///
+/// ```
+/// #[divan::bench(max_allocs = 0)]
+/// fn sum() -> u64 {
+///     (0..100).map(divan::black_box).sum()
+/// }
+/// ```
+///
+/// When the limit is exceeded, the failure message lists the number and size
+/// of each allocation operation. The limit can also be set with
+/// [`Bencher::assert_allocs`].
+///
+/// ## `max_retained_bytes`
+/// [`max_retained_bytes`]: #max_retained_bytes
+///
+/// Fails the benchmark if any sample keeps more than the given number of bytes
+/// allocated per iteration after its inputs and outputs are dropped. This
+/// catches benchmarks that leak or grow caches across iterations:
+///
+/// ```
+/// #[divan::bench(max_retained_bytes = 0)]
+/// fn parse() -> Vec<u32> {
+///     "1,2,3".split(',').map(|s| s.parse().unwrap()).collect()
+/// }
+/// ```
+///
+/// Like [`max_allocs`], this requires [`AllocProfiler`] and is also checked
+/// when running benchmarks as tests. Memory kept by one-time initialization,
+/// such as lazily-initialized statics, counts towards the sample in which it
+/// happens.
+///
+/// ## `alloc_profile`
+/// [`alloc_profile`]: #alloc_profile
+///
+/// Sets whether [`AllocProfiler`] tallies allocations while the benchmark
+/// runs. This is `true` by default.
+///
+/// Tallying adds a small cost to every allocation, which Divan only subtracts
+/// approximately from sample times. Setting this to `false` makes
+/// [`AllocProfiler`] forward directly to the wrapped allocator, so that timings
+/// are not affected and no allocation statistics are reported:
+///
+/// ```
+/// #[divan::bench(alloc_profile = false)]
+/// fn collect() -> Vec<u32> {
+///     (0..100).collect()
+/// }
+/// ```
+///
+/// Limits like [`max_allocs`] are not checked when profiling is disabled.
+///
+/// This option can be overridden for all benchmarks with the
+/// `--alloc-profile=on|off` CLI argument or `DIVAN_ALLOC_PROFILE` environment
+/// variable, which makes it possible to get clean timings and allocation
+/// statistics from separate runs of the same binary.
+///
 /// ## `ignore`
 /// [`ignore`]: #ignore
 ///
@@ -963,6 +1122,7 @@ pub use divan_macros::bench;
 /// - [`sample_count`]
 /// - [`sample_size`]
 /// - [`threads`]
+/// - [`pin`]
 /// - [`counters`]
 ///     - [`bytes_count`]
 ///     - [`chars_count`]
@@ -970,6 +1130,9 @@ pub use divan_macros::bench;
 /// - [`min_time`]
 /// - [`max_time`]
 /// - [`skip_ext_time`]
+/// - [`max_allocs`]
+/// - [`max_retained_bytes`]
+/// - [`alloc_profile`]
 /// - [`ignore`]
 ///
 /// ## `name`
@@ -1057,6 +1220,11 @@ pub use divan_macros::bench;
 ///
 /// See [`#[divan::bench(threads = ...)]`](macro@bench#threads).
 ///
+/// ## `pin`
+/// [`pin`]: #pin
+///
+/// See [`#[divan::bench(pin = ...)]`](macro@bench#pin).
+///
 /// ## `counters`
 /// [`counters`]: #counters
 ///
@@ -1271,6 +1439,50 @@ pub use divan_macros::bench;
 /// }
 /// ```
 ///
+/// ## `max_allocs`
+/// [`max_allocs`]: #max_allocs
+///
+/// Fails benchmarks if any iteration allocates more than the given number of
+/// times. See [`#[divan::bench(max_allocs = ...)]`](macro@bench#max_allocs)
+/// for details.
+///
+/// ```
+/// #[divan::bench_group(max_allocs = 0)]
+/// mod no_alloc {
+///     // ...
+/// }
+/// ```
+///
+/// ## `max_retained_bytes`
+/// [`max_retained_bytes`]: #max_retained_bytes
+///
+/// Fails benchmarks if any sample keeps more than the given number of bytes
+/// allocated per iteration. See
+/// [`#[divan::bench(max_retained_bytes = ...)]`](macro@bench#max_retained_bytes)
+/// for details.
+///
+/// ```
+/// #[divan::bench_group(max_retained_bytes = 0)]
+/// mod no_leaks {
+///     // ...
+/// }
+/// ```
+///
+/// ## `alloc_profile`
+/// [`alloc_profile`]: #alloc_profile
+///
+/// Sets whether [`AllocProfiler`] tallies allocations for benchmarks in the
+/// group. See
+/// [`#[divan::bench(alloc_profile = ...)]`](macro@bench#alloc_profile) for
+/// details.
+///
+/// ```
+/// #[divan::bench_group(alloc_profile = false)]
+/// mod timing_only {
+///     // ...
+/// }
+/// ```
+///
 /// ## `ignore`
 /// [`ignore`]: #ignore
 ///
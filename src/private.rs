@@ -1,6 +1,6 @@
 use std::{
     borrow::{Borrow, Cow},
-    fmt::Debug,
+    fmt::{Debug, Display},
 };
 
 pub use crate::{
@@ -37,6 +37,39 @@ impl<T: ToString> ToStringHelper<'_, T> {
     }
 }
 
+/// Helper to fail benchmarks that return `Err`.
+///
+/// Like `ToStringHelper`, this has an inherent `check` method for `Result`
+/// values whose error implements `Display`, which fails the benchmark on `Err`.
+/// Any other value is returned as-is by the `ResultHelperFallback` trait
+/// method, which must be in scope.
+pub struct ResultHelper<T>(pub T);
+
+impl<T, E: Display> ResultHelper<Result<T, E>> {
+    #[inline]
+    pub fn check(self) -> T {
+        match self.0 {
+            Ok(value) => value,
+            Err(error) => crate::benchmark::fail(&error),
+        }
+    }
+}
+
+pub trait ResultHelperFallback {
+    type Output;
+
+    fn check(self) -> Self::Output;
+}
+
+impl<T> ResultHelperFallback for ResultHelper<T> {
+    type Output = T;
+
+    #[inline]
+    fn check(self) -> T {
+        self.0
+    }
+}
+
 /// Used by `#[divan::bench(args = ...)]` to enable polymorphism.
 pub trait Arg<T> {
     fn get(this: Self) -> T;
@@ -161,6 +194,46 @@ where
     }
 }
 
+use crate::benchmark::CpuPin;
+
+/// Used by `#[divan::bench(pin = ...)]`.
+///
+/// This uses the same trick as [`IntoThreads`] to be polymorphic over presets
+/// and CPU lists.
+pub trait IntoCpuPin<const IMP: u32> {
+    fn into_cpu_pin(self) -> CpuPin;
+}
+
+impl IntoCpuPin<0> for CpuPin {
+    #[inline]
+    fn into_cpu_pin(self) -> CpuPin {
+        self
+    }
+}
+
+impl IntoCpuPin<0> for &str {
+    #[track_caller]
+    fn into_cpu_pin(self) -> CpuPin {
+        match self.parse() {
+            Ok(pin) => pin,
+            Err(error) => panic!("invalid `pin` option: {error}"),
+        }
+    }
+}
+
+impl<I> IntoCpuPin<1> for I
+where
+    I: IntoIterator,
+    I::Item: Borrow<usize>,
+{
+    #[inline]
+    fn into_cpu_pin(self) -> CpuPin {
+        CpuPin::Cpus(Cow::Owned(
+            self.into_iter().map(|i| *i.borrow()).collect(),
+        ))
+    }
+}
+
 /// Used by `#[divan::bench(counters = [...])]`.
 #[inline]
 pub fn new_counter_set() -> crate::counter::CounterSet {
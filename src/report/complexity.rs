@@ -0,0 +1,282 @@
+use std::fmt;
+
+/// Asymptotic complexity class.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BigO {
+    /// O(1).
+    Constant,
+
+    /// O(log n).
+    Logarithmic,
+
+    /// O(n).
+    Linear,
+
+    /// O(n log n).
+    Linearithmic,
+
+    /// O(n²).
+    Quadratic,
+}
+
+impl BigO {
+    const ALL: [Self; 5] = [
+        Self::Constant,
+        Self::Logarithmic,
+        Self::Linear,
+        Self::Linearithmic,
+        Self::Quadratic,
+    ];
+
+    /// Evaluates the complexity function at `n`.
+    #[inline]
+    fn eval(self, n: f64) -> f64 {
+        match self {
+            Self::Constant => 1.0,
+            Self::Logarithmic => n.log2(),
+            Self::Linear => n,
+            Self::Linearithmic => n * n.log2(),
+            Self::Quadratic => n * n,
+        }
+    }
+
+    /// The complexity function, without `O(...)`.
+    pub(crate) fn term(self) -> &'static str {
+        match self {
+            Self::Constant => "1",
+            Self::Logarithmic => "log n",
+            Self::Linear => "n",
+            Self::Linearithmic => "n log n",
+            Self::Quadratic => "n²",
+        }
+    }
+}
+
+impl fmt::Display for BigO {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "O({})", self.term())
+    }
+}
+
+/// Best fit of measurements to a [`BigO`] complexity class.
+///
+/// Measurements are modeled as `intercept + coefficient * f(n)`, where `n` is
+/// either the numeric value of [`args`](macro@crate::bench#args) or the number
+/// of values processed by a [counter](crate::counter). The intercept accounts
+/// for constant overhead, such as setup within the benchmarked function.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ComplexityFit {
+    pub(crate) big_o: BigO,
+    pub(crate) coefficient: f64,
+    pub(crate) intercept: f64,
+    pub(crate) rms: f64,
+}
+
+impl ComplexityFit {
+    /// Fits `(n, value)` points with the least squared error.
+    ///
+    /// Classes that grow with `n` only fit if their coefficient is positive,
+    /// and are only preferred over [`BigO::Constant`] if they fit
+    /// significantly better.
+    ///
+    /// Points with `n < 1` are skipped. Returns `None` if there are fewer than
+    /// 3 distinct values of `n`.
+    pub(crate) fn fit(points: &[(f64, f64)]) -> Option<Self> {
+        let points: Vec<(f64, f64)> = points
+            .iter()
+            .copied()
+            .filter(|&(n, value)| n >= 1.0 && value.is_finite())
+            .collect();
+
+        let mut distinct_n: Vec<f64> = points.iter().map(|&(n, _)| n).collect();
+        distinct_n.sort_unstable_by(f64::total_cmp);
+        distinct_n.dedup();
+        if distinct_n.len() < 3 {
+            return None;
+        }
+
+        let count = points.len() as f64;
+        let mean = points.iter().map(|&(_, value)| value).sum::<f64>() / count;
+        if mean <= 0.0 {
+            return None;
+        }
+
+        let sum_sq_err = |fit: &Self| -> f64 {
+            points.iter().map(|&(n, value)| (value - fit.eval(n)).powi(2)).sum()
+        };
+
+        let with_rms = |mut fit: Self, sum_sq_err: f64| -> Self {
+            fit.rms = (sum_sq_err / count).sqrt() / mean;
+            fit
+        };
+
+        let constant = Self {
+            big_o: BigO::Constant,
+            coefficient: mean,
+            intercept: 0.0,
+            rms: 0.0,
+        };
+        let constant_sq_err = sum_sq_err(&constant);
+
+        // Least squares for `value = intercept + coefficient * f(n)` of each
+        // growing class, keeping the one with the least error.
+        let growing = BigO::ALL
+            .into_iter()
+            .filter(|&big_o| big_o != BigO::Constant)
+            .filter_map(|big_o| {
+                let mean_f =
+                    points.iter().map(|&(n, _)| big_o.eval(n)).sum::<f64>()
+                        / count;
+
+                let (mut sum_fv, mut sum_ff) = (0.0, 0.0);
+                for &(n, value) in &points {
+                    let f = big_o.eval(n) - mean_f;
+                    sum_fv += f * (value - mean);
+                    sum_ff += f * f;
+                }
+
+                if sum_ff == 0.0 {
+                    return None;
+                }
+
+                let coefficient = sum_fv / sum_ff;
+                if coefficient <= 0.0 {
+                    return None;
+                }
+
+                let intercept = mean - coefficient * mean_f;
+                let fit = Self { big_o, coefficient, intercept, rms: 0.0 };
+
+                Some((sum_sq_err(&fit), fit))
+            })
+            .min_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        let Some((growing_sq_err, growing)) = growing else {
+            return Some(with_rms(constant, constant_sq_err));
+        };
+
+        // Growing classes have an extra parameter, so they always fit at least
+        // as well as a constant. Only prefer them if they explain significantly
+        // more of the variation than noise would, by an F-test.
+        let residual_dof = count - 2.0;
+        let is_significant = growing_sq_err == 0.0
+            || (residual_dof > 0.0
+                && (constant_sq_err - growing_sq_err)
+                    / (growing_sq_err / residual_dof)
+                    > Self::MIN_F_STATISTIC);
+
+        Some(if is_significant && constant_sq_err > 0.0 {
+            with_rms(growing, growing_sq_err)
+        } else {
+            with_rms(constant, constant_sq_err)
+        })
+    }
+
+    /// The F-statistic above which a growing class is preferred over
+    /// [`BigO::Constant`], roughly a 5-10% significance level.
+    const MIN_F_STATISTIC: f64 = 5.0;
+
+    /// Evaluates the fitted model at `n`.
+    #[inline]
+    fn eval(&self, n: f64) -> f64 {
+        self.intercept + self.coefficient * self.big_o.eval(n)
+    }
+
+    /// The complexity class that best fits the measurements.
+    #[inline]
+    pub fn big_o(&self) -> BigO {
+        self.big_o
+    }
+
+    /// The constant factor multiplied by the complexity function.
+    ///
+    /// This is in nanoseconds for time and bytes for allocations.
+    #[inline]
+    pub fn coefficient(&self) -> f64 {
+        self.coefficient
+    }
+
+    /// The constant overhead added to the complexity function, in the same
+    /// unit as [`coefficient`](Self::coefficient).
+    ///
+    /// This is 0 for [`BigO::Constant`], where the coefficient is the whole
+    /// measurement.
+    #[inline]
+    pub fn intercept(&self) -> f64 {
+        self.intercept
+    }
+
+    /// Root-mean-square error of the fit, relative to the mean measurement.
+    #[inline]
+    pub fn normalized_rms(&self) -> f64 {
+        self.rms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn test(f: impl Fn(f64) -> f64, expected: BigO, intercept: f64) {
+        let points: Vec<(f64, f64)> = [1, 2, 4, 8, 16, 64, 256, 1024]
+            .into_iter()
+            .map(|n| (n as f64, f(n as f64)))
+            .collect();
+
+        let fit = ComplexityFit::fit(&points).unwrap();
+        assert_eq!(fit.big_o, expected);
+        assert!((fit.coefficient - 3.0).abs() < 1e-6, "{fit:?}");
+        assert!((fit.intercept - intercept).abs() < 1e-6, "{fit:?}");
+    }
+
+    #[test]
+    fn fit() {
+        test(|_| 3.0, BigO::Constant, 0.0);
+        test(|n| 3.0 * n.log2(), BigO::Logarithmic, 0.0);
+        test(|n| 3.0 * n, BigO::Linear, 0.0);
+        test(|n| 3.0 * n * n.log2(), BigO::Linearithmic, 0.0);
+        test(|n| 3.0 * n * n, BigO::Quadratic, 0.0);
+    }
+
+    #[test]
+    fn fit_constant_overhead() {
+        test(|n| 1000.0 + 3.0 * n.log2(), BigO::Logarithmic, 1000.0);
+        test(|n| 1000.0 + 3.0 * n, BigO::Linear, 1000.0);
+        test(|n| 1000.0 + 3.0 * n * n.log2(), BigO::Linearithmic, 1000.0);
+        test(|n| 1000.0 + 3.0 * n * n, BigO::Quadratic, 1000.0);
+    }
+
+    #[test]
+    fn fit_noisy_constant() {
+        // Noise that slightly increases with `n`.
+        for noise in [
+            [10.2, 9.9, 10.1, 9.8, 10.0, 10.3, 9.7, 10.0],
+            [9.9, 10.1, 9.8, 10.2, 9.9, 10.0, 10.1, 10.05],
+        ] {
+            let points: Vec<(f64, f64)> = [1, 2, 4, 8, 16, 64, 256, 1024]
+                .into_iter()
+                .zip(noise)
+                .map(|(n, value)| (n as f64, value))
+                .collect();
+
+            let fit = ComplexityFit::fit(&points).unwrap();
+            assert_eq!(fit.big_o, BigO::Constant, "{fit:?}");
+            assert_eq!(fit.intercept, 0.0);
+        }
+    }
+
+    #[test]
+    fn fit_too_few_points() {
+        assert_eq!(ComplexityFit::fit(&[]), None);
+        assert_eq!(
+            ComplexityFit::fit(&[(0.0, 1.0), (1.0, 1.0), (2.0, 2.0)]),
+            None
+        );
+        assert_eq!(
+            ComplexityFit::fit(&[(2.0, 1.0), (2.0, 1.0), (4.0, 2.0)]),
+            None
+        );
+    }
+}
@@ -0,0 +1,335 @@
+//! Criterion-compatible output directory.
+//!
+//! This mirrors the files that Criterion writes for each benchmark so that
+//! tools like `critcmp` can read Divan results:
+//!
+//! ```txt
+//! <dir>/<group>/<function>/{new,base}/{benchmark,estimates,sample}.json
+//! ```
+
+use std::{
+    env, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::{
+    counter::{BytesCount, ItemsCount},
+    report::{
+        json::{JsonNum, JsonStr},
+        BenchReport, BenchStats, Report, ReportNode,
+    },
+};
+
+/// The maximum length of a directory name, as defined by Criterion.
+const MAX_DIRECTORY_NAME_LEN: usize = 64;
+
+/// Returns `target/criterion` for the current Cargo workspace.
+pub(crate) fn default_dir() -> PathBuf {
+    let target_dir = env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .or_else(cargo_target_dir)
+        .unwrap_or_else(|| PathBuf::from("target"));
+
+    target_dir.join("criterion")
+}
+
+/// Gets the target directory from `cargo metadata`, like Criterion does.
+fn cargo_target_dir() -> Option<PathBuf> {
+    let cargo = env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+
+    let output = Command::new(cargo)
+        .args(["metadata", "--format-version", "1", "--no-deps"])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let metadata = String::from_utf8(output.stdout).ok()?;
+    parse_str_field(&metadata, "target_directory").map(PathBuf::from)
+}
+
+/// Parses the string value of the first `"key":` in `json`.
+fn parse_str_field(json: &str, key: &str) -> Option<String> {
+    let key = format!("\"{key}\":");
+    let value = json[json.find(&key)? + key.len()..].trim_start();
+    let mut chars = value.strip_prefix('"')?.chars();
+
+    let mut result = String::new();
+    loop {
+        match chars.next()? {
+            '"' => return Some(result),
+            '\\' => result.push(match chars.next()? {
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                'u' => {
+                    let hex: String = chars.by_ref().take(4).collect();
+                    char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?
+                }
+                ch => ch,
+            }),
+            ch => result.push(ch),
+        }
+    }
+}
+
+/// Writes results for every measured benchmark in `report` into `dir`.
+pub(crate) fn write_dir(report: &Report, dir: &Path) -> io::Result<()> {
+    write_nodes(report.nodes(), None, dir)
+}
+
+fn write_nodes(
+    nodes: &[ReportNode],
+    group_id: Option<&str>,
+    dir: &Path,
+) -> io::Result<()> {
+    for node in nodes {
+        match node {
+            ReportNode::Group(group) => {
+                write_nodes(group.children(), Some(group.path()), dir)?;
+            }
+            ReportNode::Bench(bench) => {
+                if let Some(stats) = bench.stats() {
+                    write_bench(bench, stats, group_id, dir)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn write_bench(
+    bench: &BenchReport,
+    stats: &BenchStats,
+    group_id: Option<&str>,
+    dir: &Path,
+) -> io::Result<()> {
+    // Parents become the group and the leaf becomes the function.
+    let (group_id, function_id) = match group_id {
+        Some(group_id) => (group_id, Some(bench.name())),
+        None => (bench.name(), None),
+    };
+
+    let mut full_id = group_id.to_owned();
+    let mut directory_name = make_filename_safe(group_id);
+
+    if let Some(function_id) = function_id {
+        full_id.push('/');
+        full_id.push_str(function_id);
+
+        directory_name.push('/');
+        directory_name.push_str(&make_filename_safe(function_id));
+    }
+
+    let mut benchmark = Vec::new();
+    write_benchmark(
+        &mut benchmark,
+        stats,
+        group_id,
+        function_id,
+        &full_id,
+        &directory_name,
+    )?;
+
+    let mut estimates = Vec::new();
+    write_estimates(&mut estimates, stats)?;
+
+    let mut sample = Vec::new();
+    write_sample(&mut sample, stats)?;
+
+    // Criterion saves the latest results as both `new` and the default `base`
+    // baseline.
+    for baseline in ["new", "base"] {
+        let baseline_dir = dir.join(&directory_name).join(baseline);
+        fs::create_dir_all(&baseline_dir)?;
+
+        fs::write(baseline_dir.join("benchmark.json"), &benchmark)?;
+        fs::write(baseline_dir.join("estimates.json"), &estimates)?;
+        fs::write(baseline_dir.join("sample.json"), &sample)?;
+    }
+
+    Ok(())
+}
+
+fn write_benchmark(
+    writer: &mut dyn Write,
+    stats: &BenchStats,
+    group_id: &str,
+    function_id: Option<&str>,
+    full_id: &str,
+    directory_name: &str,
+) -> io::Result<()> {
+    write!(writer, "{{\"group_id\":{},\"function_id\":", JsonStr(group_id))?;
+
+    match function_id {
+        Some(function_id) => write!(writer, "{}", JsonStr(function_id))?,
+        None => write!(writer, "null")?,
+    }
+
+    write!(writer, ",\"value_str\":null,\"throughput\":")?;
+
+    let bytes = stats.counter::<BytesCount>();
+    let items = stats.counter::<ItemsCount>();
+
+    // Criterion supports only one throughput per benchmark.
+    match (bytes, items) {
+        (Some(bytes), _) => write!(writer, "{{\"Bytes\":{}}}", bytes.median)?,
+        (None, Some(items)) => {
+            write!(writer, "{{\"Elements\":{}}}", items.median)?
+        }
+        (None, None) => write!(writer, "null")?,
+    }
+
+    write!(
+        writer,
+        ",\"full_id\":{},\"directory_name\":{},\"title\":{}}}",
+        JsonStr(full_id),
+        JsonStr(directory_name),
+        JsonStr(full_id),
+    )
+}
+
+fn write_sample(writer: &mut dyn Write, stats: &BenchStats) -> io::Result<()> {
+    let sample_size = stats.sample_size();
+
+    // Divan uses the same number of iterations for every sample.
+    write!(writer, "{{\"sampling_mode\":\"Flat\",\"iters\":[")?;
+    for i in 0..stats.sample_count() {
+        let sep = if i == 0 { "" } else { "," };
+        write!(writer, "{sep}{}", JsonNum(f64::from(sample_size)))?;
+    }
+
+    write!(writer, "],\"times\":[")?;
+    for (i, time) in stats.sample_times_nanos().enumerate() {
+        let sep = if i == 0 { "" } else { "," };
+        write!(writer, "{sep}{}", JsonNum(time))?;
+    }
+
+    write!(writer, "]}}")
+}
+
+fn write_estimates(
+    writer: &mut dyn Write,
+    stats: &BenchStats,
+) -> io::Result<()> {
+    let sample_size = f64::from(stats.sample_size());
+
+    let mut times: Vec<f64> =
+        stats.sample_times_nanos().map(|time| time / sample_size).collect();
+    times.sort_unstable_by(f64::total_cmp);
+
+    let n = times.len() as f64;
+    let mean = times.iter().sum::<f64>() / n;
+    let median = sorted_median(&times);
+
+    let std_dev = if times.len() > 1 {
+        let sum_sq: f64 = times.iter().map(|t| (t - mean).powi(2)).sum();
+        (sum_sq / (n - 1.0)).sqrt()
+    } else {
+        0.0
+    };
+
+    // Scaled to be consistent with standard deviation, like Criterion.
+    let median_abs_dev = {
+        let mut deviations: Vec<f64> =
+            times.iter().map(|t| (t - median).abs()).collect();
+        deviations.sort_unstable_by(f64::total_cmp);
+        sorted_median(&deviations) * 1.4826
+    };
+
+    // Standard errors are approximated from the normal distribution rather
+    // than bootstrapped.
+    let spread_se =
+        if times.len() > 1 { (2.0 * (n - 1.0)).sqrt() } else { 1.0 };
+    let estimates = [
+        ("mean", mean, std_dev / n.sqrt()),
+        ("median", median, 1.2533 * std_dev / n.sqrt()),
+        ("median_abs_dev", median_abs_dev, median_abs_dev / spread_se),
+        ("std_dev", std_dev, std_dev / spread_se),
+    ];
+
+    write!(writer, "{{")?;
+
+    for (name, point, standard_error) in estimates {
+        // 95% confidence interval.
+        let margin = 1.96 * standard_error;
+
+        write!(
+            writer,
+            "{}:{{\"confidence_interval\":{{\"confidence_level\":0.95,\"lower_bound\":{},\"upper_bound\":{}}},\"point_estimate\":{},\"standard_error\":{}}},",
+            JsonStr(name),
+            JsonNum(point - margin),
+            JsonNum(point + margin),
+            JsonNum(point),
+            JsonNum(standard_error),
+        )?;
+    }
+
+    // Slope is only estimated for linear sampling.
+    write!(writer, "\"slope\":null}}")
+}
+
+fn sorted_median(values: &[f64]) -> f64 {
+    match values.len() {
+        0 => 0.0,
+        len if len % 2 == 0 => (values[len / 2 - 1] + values[len / 2]) / 2.0,
+        len => values[len / 2],
+    }
+}
+
+/// Replaces characters that are invalid in file names, like Criterion.
+fn make_filename_safe(name: &str) -> String {
+    let mut name =
+        name.replace(['?', '"', '/', '\\', '*', '<', '>', ':', '|', '^'], "_");
+
+    if name.len() > MAX_DIRECTORY_NAME_LEN {
+        let mut boundary = MAX_DIRECTORY_NAME_LEN;
+        while !name.is_char_boundary(boundary) {
+            boundary -= 1;
+        }
+        name.truncate(boundary);
+    }
+
+    // File names are case-insensitive on Windows.
+    if cfg!(windows) {
+        name = name.to_lowercase();
+    }
+
+    name.trim().to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filename_safe() {
+        assert_eq!(make_filename_safe("math::add"), "math__add");
+        assert_eq!(make_filename_safe("a/b\\c"), "a_b_c");
+        assert_eq!(make_filename_safe(&"é".repeat(40)).len(), 64);
+    }
+
+    #[test]
+    fn parse_target_directory() {
+        let json =
+            r#"{"packages":[],"target_directory":"C:\\a\"b\u0063","x":1}"#;
+        assert_eq!(
+            parse_str_field(json, "target_directory").as_deref(),
+            Some(r#"C:\a"bc"#)
+        );
+        assert_eq!(parse_str_field(json, "missing"), None);
+        assert_eq!(parse_str_field(json, "x"), None);
+    }
+
+    #[test]
+    fn median() {
+        assert_eq!(sorted_median(&[]), 0.0);
+        assert_eq!(sorted_median(&[1.0, 2.0, 4.0]), 2.0);
+        assert_eq!(sorted_median(&[1.0, 2.0, 4.0, 8.0]), 3.0);
+    }
+}
@@ -0,0 +1,173 @@
+use std::io::{self, Write};
+
+use crate::{
+    counter::KnownCounterKind,
+    report::{
+        json::{JsonNum, JsonStr},
+        BenchReport, BenchStats, Report,
+    },
+    stats::StatsSet,
+    util,
+};
+
+/// Machine-readable format for benchmark results.
+///
+/// Benchmarks are identified by the same `::`-joined paths that are listed by
+/// `--list`, with `t=N` appended for benchmarks run over multiple thread
+/// counts. Ignored and unmeasured benchmarks are omitted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OutputFormat {
+    /// [Bencher Metric Format](https://bencher.dev/docs/reference/bencher-metric-format/)
+    /// JSON.
+    ///
+    /// Time is reported as the `latency` measure in nanoseconds, where the
+    /// value is the median and the bounds are the fastest and slowest
+    /// samples. Each [counter](crate::counter) is reported as a separate
+    /// `throughput-{bytes,chars,cycles,items}` measure per second.
+    Bmf,
+
+    /// [github-action-benchmark](https://github.com/benchmark-action/github-action-benchmark)
+    /// `customSmallerIsBetter` JSON.
+    ///
+    /// Each entry is the median time in nanoseconds. Throughput is not
+    /// included because larger values are better.
+    GithubActionBenchmark,
+}
+
+impl OutputFormat {
+    pub(crate) fn write(
+        self,
+        report: &Report,
+        mut writer: impl Write,
+    ) -> io::Result<()> {
+        let writer = &mut writer as &mut dyn Write;
+
+        let mut benches = report
+            .benches()
+            .filter_map(|bench| bench.stats().map(|stats| (bench, stats)));
+
+        match self {
+            Self::Bmf => write_bmf(&mut benches, writer),
+            Self::GithubActionBenchmark => write_gab(&mut benches, writer),
+        }
+    }
+}
+
+type MeasuredBenches<'a> =
+    dyn Iterator<Item = (&'a BenchReport, &'a BenchStats)> + 'a;
+
+fn write_bmf(
+    benches: &mut MeasuredBenches,
+    writer: &mut dyn Write,
+) -> io::Result<()> {
+    writeln!(writer, "{{")?;
+
+    let mut is_first_bench = true;
+    for (bench, stats) in benches {
+        if !is_first_bench {
+            writeln!(writer, ",")?;
+        }
+        is_first_bench = false;
+
+        write!(writer, "  {}: {{", JsonStr(bench.path()))?;
+
+        let time = stats.time_nanos();
+        write_bmf_measure(
+            writer,
+            "latency",
+            time.median,
+            time.fastest,
+            time.slowest,
+        )?;
+
+        for counter_kind in KnownCounterKind::ALL {
+            let Some(throughput) = stats.throughput_of(counter_kind) else {
+                continue;
+            };
+
+            let name = match counter_kind {
+                KnownCounterKind::Bytes => "throughput-bytes",
+                KnownCounterKind::Chars => "throughput-chars",
+                KnownCounterKind::Cycles => "throughput-cycles",
+                KnownCounterKind::Items => "throughput-items",
+            };
+
+            writeln!(writer, ",")?;
+
+            // Slowest time has the lowest throughput.
+            write_bmf_measure(
+                writer,
+                name,
+                throughput.median,
+                throughput.slowest,
+                throughput.fastest,
+            )?;
+        }
+
+        write!(writer, "\n  }}")?;
+    }
+
+    if !is_first_bench {
+        writeln!(writer)?;
+    }
+
+    writeln!(writer, "}}")
+}
+
+fn write_bmf_measure(
+    writer: &mut dyn Write,
+    name: &str,
+    value: f64,
+    lower_value: f64,
+    upper_value: f64,
+) -> io::Result<()> {
+    write!(
+        writer,
+        "\n    {}: {{ \"value\": {}, \"lower_value\": {}, \"upper_value\": {} }}",
+        JsonStr(name),
+        JsonNum(value),
+        JsonNum(lower_value),
+        JsonNum(upper_value),
+    )
+}
+
+fn write_gab(
+    benches: &mut MeasuredBenches,
+    writer: &mut dyn Write,
+) -> io::Result<()> {
+    writeln!(writer, "[")?;
+
+    let mut is_first_bench = true;
+    for (bench, stats) in benches {
+        if !is_first_bench {
+            writeln!(writer, ",")?;
+        }
+        is_first_bench = false;
+
+        let StatsSet { fastest, slowest, median, mean } = stats.time_nanos();
+
+        let extra = format!(
+            "fastest: {} ns\nslowest: {} ns\nmean: {} ns\nsamples: {}\niters: {}",
+            util::fmt::format_f64(fastest, 4),
+            util::fmt::format_f64(slowest, 4),
+            util::fmt::format_f64(mean, 4),
+            stats.sample_count(),
+            stats.iter_count(),
+        );
+
+        write!(
+            writer,
+            "  {{ \"name\": {}, \"unit\": \"ns\", \"value\": {}, \"extra\": {} }}",
+            JsonStr(bench.path()),
+            JsonNum(median),
+            JsonStr(&extra),
+        )?;
+    }
+
+    if !is_first_bench {
+        writeln!(writer)?;
+    }
+
+    writeln!(writer, "]")
+}
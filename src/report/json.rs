@@ -0,0 +1,75 @@
+//! Minimal JSON serialization helpers.
+
+use std::fmt;
+
+/// Formats a JSON string literal.
+pub(crate) struct JsonStr<'a>(pub &'a str);
+
+impl fmt::Display for JsonStr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use std::fmt::Write;
+
+        f.write_char('"')?;
+
+        for ch in self.0.chars() {
+            match ch {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                '\n' => f.write_str("\\n")?,
+                '\r' => f.write_str("\\r")?,
+                '\t' => f.write_str("\\t")?,
+                ch if ch.is_control() => write!(f, "\\u{:04x}", ch as u32)?,
+                ch => f.write_char(ch)?,
+            }
+        }
+
+        f.write_char('"')
+    }
+}
+
+/// Formats a JSON number, where non-finite values become 0.
+pub(crate) struct JsonNum(pub f64);
+
+impl fmt::Display for JsonNum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_finite() {
+            write!(f, "{}", self.0)
+        } else {
+            f.write_str("0")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_str() {
+        #[track_caller]
+        fn test(s: &str, expected: &str) {
+            assert_eq!(JsonStr(s).to_string(), expected);
+        }
+
+        test("", r#""""#);
+        test("a::b", r#""a::b""#);
+        test(r#""quoted""#, r#""\"quoted\"""#);
+        test(r"back\slash", r#""back\\slash""#);
+        test("line\nbreak", r#""line\nbreak""#);
+        test("\u{1}", r#""\u0001""#);
+    }
+
+    #[test]
+    fn json_num() {
+        #[track_caller]
+        fn test(n: f64, expected: &str) {
+            assert_eq!(JsonNum(n).to_string(), expected);
+        }
+
+        test(0.0, "0");
+        test(1.5, "1.5");
+        test(1e-7, "0.0000001");
+        test(f64::INFINITY, "0");
+        test(f64::NAN, "0");
+    }
+}
@@ -0,0 +1,1275 @@
+//! Programmatic access to benchmark results.
+//!
+//! # Examples
+//!
+//! [`Divan::run_benches`](crate::Divan::run_benches) returns a [`Report`] that
+//! can be post-processed after all benchmarks finish:
+//!
+//! ```
+//! use divan::Divan;
+//!
+//! let report = Divan::default().run_benches();
+//!
+//! for bench in report.benches() {
+//!     if let Some(stats) = bench.stats() {
+//!         println!("{}: {:?}", bench.path(), stats.time().median);
+//!     }
+//! }
+//! ```
+
+use std::{io, path::Path, time::Duration};
+
+use crate::{
+    alloc::AllocOp,
+    benchmark::{BenchOptions, CpuPin},
+    counter::{IntoCounter, KnownCounterKind, MaxCountUInt},
+    stats::Stats,
+    time::{FineDuration, Timer},
+};
+
+mod complexity;
+mod criterion;
+mod format;
+mod json;
+mod scaling;
+
+pub(crate) use criterion::default_dir as default_criterion_dir;
+
+pub use complexity::{BigO, ComplexityFit};
+pub use format::OutputFormat;
+pub use scaling::ThreadScaling;
+
+pub use crate::{
+    alloc::AllocTally,
+    stats::{Distribution, StatsSet},
+};
+
+/// Results of running benchmarks.
+///
+/// This is returned by [`Divan::run_benches`](crate::Divan::run_benches).
+#[derive(Clone, Debug, Default)]
+pub struct Report {
+    pub(crate) timer: Option<TimerInfo>,
+    pub(crate) nodes: Vec<ReportNode>,
+}
+
+impl Report {
+    /// Information about the timer used to measure samples.
+    ///
+    /// This is `None` if no benchmarks were run.
+    #[inline]
+    pub fn timer(&self) -> Option<&TimerInfo> {
+        self.timer.as_ref()
+    }
+
+    /// The top-level nodes of the benchmark tree.
+    #[inline]
+    pub fn nodes(&self) -> &[ReportNode] {
+        &self.nodes
+    }
+
+    /// Writes the results of measured benchmarks in the given format.
+    pub fn write(
+        &self,
+        format: OutputFormat,
+        writer: impl io::Write,
+    ) -> io::Result<()> {
+        format.write(self, writer)
+    }
+
+    /// Prints a summary and panics if any benchmark returned an error or
+    /// panicked.
+    pub(crate) fn assert_no_failures(&self) {
+        let failures: Vec<(&str, &str, &str)> = self
+            .benches()
+            .filter_map(|bench| {
+                if let Some(error) = bench.error() {
+                    Some((bench.path(), "error", error))
+                } else {
+                    bench
+                        .panic_message()
+                        .map(|message| (bench.path(), "panicked", message))
+                }
+            })
+            .collect();
+
+        if failures.is_empty() {
+            return;
+        }
+
+        eprintln!("\nfailures:");
+        for (path, kind, message) in &failures {
+            eprintln!("    {path}: {kind}: {message}");
+        }
+        eprintln!();
+
+        panic!("{} benchmark(s) failed", failures.len());
+    }
+
+    /// Writes the results of measured benchmarks into `dir` in the layout used
+    /// by [Criterion](https://github.com/bheisler/criterion.rs), such as
+    /// `target/criterion`.
+    ///
+    /// Parent nodes become groups and benchmarks become functions, so
+    /// `math::fibonacci::iterative::5` is written to
+    /// `math__fibonacci__iterative/5/new/estimates.json`. Estimates use
+    /// normal-distribution confidence intervals rather than bootstrapping.
+    pub fn write_criterion_dir(&self, dir: impl AsRef<Path>) -> io::Result<()> {
+        criterion::write_dir(self, dir.as_ref())
+    }
+
+    /// Returns an iterator over all benchmarks in the tree, in the order they
+    /// were run.
+    pub fn benches(&self) -> impl Iterator<Item = &BenchReport> {
+        let mut stack: Vec<std::slice::Iter<ReportNode>> =
+            vec![self.nodes.iter()];
+
+        std::iter::from_fn(move || loop {
+            let iter = stack.last_mut()?;
+
+            match iter.next() {
+                Some(ReportNode::Bench(bench)) => return Some(bench),
+                Some(ReportNode::Group(group)) => {
+                    stack.push(group.children.iter())
+                }
+                None => _ = stack.pop(),
+            }
+        })
+    }
+}
+
+/// Information about the timer used to measure samples.
+#[derive(Clone, Debug)]
+pub struct TimerInfo {
+    timer: Timer,
+}
+
+impl TimerInfo {
+    #[inline]
+    pub(crate) fn new(timer: Timer) -> Self {
+        Self { timer }
+    }
+
+    /// The name of the timer, as provided to the `--timer` CLI argument.
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        match self.timer {
+            Timer::Os => "os",
+            Timer::Tsc { .. } => "tsc",
+        }
+    }
+
+    /// The smallest non-zero duration that the timer can measure.
+    #[inline]
+    pub fn precision(&self) -> Duration {
+        self.timer.precision().into()
+    }
+
+    /// The frequency of the CPU timestamp counter in Hertz, if it was used.
+    #[inline]
+    pub fn tsc_frequency(&self) -> Option<u64> {
+        match self.timer {
+            Timer::Os => None,
+            Timer::Tsc { frequency } => Some(frequency.get()),
+        }
+    }
+}
+
+/// Node in the benchmark tree.
+#[derive(Clone, Debug)]
+#[allow(clippy::large_enum_variant)]
+pub enum ReportNode {
+    /// Module, group, or benchmark with multiple runs.
+    Group(GroupReport),
+
+    /// Benchmark run.
+    Bench(BenchReport),
+}
+
+impl ReportNode {
+    /// The name displayed for this node.
+    #[inline]
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Group(group) => group.name(),
+            Self::Bench(bench) => bench.name(),
+        }
+    }
+
+    /// The `::`-joined names from the root of the tree to this node.
+    #[inline]
+    pub fn path(&self) -> &str {
+        match self {
+            Self::Group(group) => group.path(),
+            Self::Bench(bench) => bench.path(),
+        }
+    }
+}
+
+/// Parent node in the benchmark tree.
+///
+/// This is either a module, a [`#[divan::bench_group]`](macro@crate::bench_group),
+/// or a benchmark run over multiple
+/// [`args`](macro@crate::bench#args) or [`threads`](macro@crate::bench#threads).
+#[derive(Clone, Debug)]
+pub struct GroupReport {
+    pub(crate) name: String,
+    pub(crate) path: String,
+    pub(crate) children: Vec<ReportNode>,
+    pub(crate) complexity: GroupComplexity,
+    pub(crate) thread_scaling: Vec<ThreadScaling>,
+}
+
+impl GroupReport {
+    /// The name displayed for this group.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The `::`-joined names from the root of the tree to this group.
+    #[inline]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The children of this group.
+    #[inline]
+    pub fn children(&self) -> &[ReportNode] {
+        &self.children
+    }
+
+    /// Best fit of median time to the benchmark's numeric arguments or
+    /// counters.
+    ///
+    /// This is only available for a benchmark run over at least 3 different
+    /// [`args`](macro@crate::bench#args) values on a single thread count.
+    #[inline]
+    pub fn time_complexity(&self) -> Option<ComplexityFit> {
+        self.complexity.time
+    }
+
+    /// Best fit of median allocated bytes to the benchmark's numeric arguments
+    /// or counters.
+    ///
+    /// This has the same requirements as
+    /// [`time_complexity`](Self::time_complexity) and also requires
+    /// [`AllocProfiler`](crate::AllocProfiler) to record allocations.
+    #[inline]
+    pub fn alloc_complexity(&self) -> Option<ComplexityFit> {
+        self.complexity.alloc
+    }
+
+    /// Speedup and efficiency of each thread count relative to 1 thread.
+    ///
+    /// This is only available for a benchmark run over multiple
+    /// [`threads`](macro@crate::bench#threads) values including 1.
+    #[inline]
+    pub fn thread_scaling(&self) -> &[ThreadScaling] {
+        &self.thread_scaling
+    }
+}
+
+/// Complexity fits for a group run over arguments.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct GroupComplexity {
+    pub time: Option<ComplexityFit>,
+    pub alloc: Option<ComplexityFit>,
+}
+
+impl GroupComplexity {
+    /// Fits the median time and allocated bytes of each benchmark run against
+    /// its argument.
+    ///
+    /// Each argument is used as `n` if all are numeric. Otherwise, the first
+    /// counter that all runs have is used.
+    pub fn fit(runs: &[(&str, &BenchStats)]) -> Self {
+        let numeric_args: Option<Vec<f64>> =
+            runs.iter().map(|(arg, _)| arg.parse::<f64>().ok()).collect();
+
+        let n_values = numeric_args.or_else(|| {
+            KnownCounterKind::ALL.into_iter().find_map(|counter_kind| {
+                runs.iter()
+                    .map(|(_, stats)| {
+                        let counts = stats.stats.get_counts(counter_kind)?;
+                        Some(counts.median as f64)
+                    })
+                    .collect()
+            })
+        });
+
+        let Some(n_values) = n_values else {
+            return Self::default();
+        };
+
+        let fit = |value: &dyn Fn(&BenchStats) -> f64| {
+            let points: Vec<(f64, f64)> = n_values
+                .iter()
+                .zip(runs)
+                .map(|(&n, (_, stats))| (n, value(stats)))
+                .collect();
+
+            ComplexityFit::fit(&points)
+        };
+
+        Self {
+            time: fit(&|stats| stats.time_nanos().median),
+            alloc: fit(&|stats| stats.alloc().size.median)
+                .filter(|fit| fit.coefficient > 0.0),
+        }
+    }
+}
+
+/// Results of a single benchmark run.
+#[derive(Clone, Debug)]
+pub struct BenchReport {
+    pub(crate) name: String,
+    pub(crate) path: String,
+    pub(crate) thread_count: usize,
+    pub(crate) pinned_cpus: Vec<usize>,
+    pub(crate) options: BenchOptions<'static>,
+    pub(crate) ignored: bool,
+    pub(crate) error: Option<String>,
+    pub(crate) panic_message: Option<String>,
+    pub(crate) stats: Option<BenchStats>,
+}
+
+impl BenchReport {
+    /// The name displayed for this benchmark.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The `::`-joined names from the root of the tree to this benchmark.
+    #[inline]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The number of threads the benchmark was run on.
+    #[inline]
+    pub fn thread_count(&self) -> usize {
+        self.thread_count
+    }
+
+    /// See [`BenchInfo::pinned_cpus`].
+    #[inline]
+    pub fn pinned_cpus(&self) -> &[usize] {
+        &self.pinned_cpus
+    }
+
+    /// The options used to run this benchmark, after combining options from
+    /// parent groups and [`Divan`](crate::Divan).
+    #[inline]
+    pub fn options(&self) -> OptionsInfo<'_> {
+        OptionsInfo { options: &self.options }
+    }
+
+    /// Whether the benchmark was skipped for being marked
+    /// [`ignore`](macro@crate::bench#ignore).
+    #[inline]
+    pub fn is_ignored(&self) -> bool {
+        self.ignored
+    }
+
+    /// Measurement statistics.
+    ///
+    /// This is `None` if the benchmark was not measured, such as when it was
+    /// ignored or run with `--test`.
+    #[inline]
+    pub fn stats(&self) -> Option<&BenchStats> {
+        self.stats.as_ref()
+    }
+
+    /// The error returned by the benchmark function, formatted with
+    /// [`Display`](std::fmt::Display).
+    ///
+    /// The benchmark is not measured if this is `Some`.
+    #[inline]
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// The panic message of the benchmark, if it panicked while running with
+    /// [`Divan::keep_going`](crate::Divan::keep_going).
+    ///
+    /// The benchmark is not measured if this is `Some`.
+    #[inline]
+    pub fn panic_message(&self) -> Option<&str> {
+        self.panic_message.as_deref()
+    }
+}
+
+/// Measurement statistics for a benchmark.
+///
+/// Allocation statistics are only recorded if
+/// [`AllocProfiler`](crate::AllocProfiler) is the global allocator. Values are
+/// per iteration.
+///
+/// Allocation statistics returned as [`StatsSet`] come from the samples with
+/// the fastest, slowest, and median times. Methods ending in `_distribution`
+/// instead return the [`Distribution`] over all samples, which shows allocation
+/// behavior that varies between samples regardless of time.
+#[derive(Clone, Debug)]
+pub struct BenchStats {
+    pub(crate) stats: Stats,
+    pub(crate) roles: Vec<RoleStats>,
+    pub(crate) threads: Vec<BenchStats>,
+}
+
+impl BenchStats {
+    /// Statistics for the threads of each role, if benchmarked with
+    /// [`Bencher::bench_roles`](crate::Bencher::bench_roles).
+    ///
+    /// Roles that were not run for lack of threads are omitted.
+    #[inline]
+    pub fn roles(&self) -> &[RoleStats] {
+        &self.roles
+    }
+
+    /// Statistics for each thread, if the benchmark ran on multiple threads
+    /// with [`Divan::thread_breakdown`](crate::Divan::thread_breakdown).
+    ///
+    /// Statistics are ordered by thread index, where 0 is the main thread.
+    #[inline]
+    pub fn threads(&self) -> &[BenchStats] {
+        &self.threads
+    }
+
+    /// The ratio of the slowest thread's mean time to the fastest thread's,
+    /// along with the indices of the fastest and slowest threads.
+    ///
+    /// A spread far above 1 indicates unfair scheduling, such as threads
+    /// starving for a lock. This is `None` without
+    /// [`threads`](Self::threads), or if the fastest thread's mean time is 0,
+    /// such as when it is below timer precision.
+    pub fn thread_spread(&self) -> Option<ThreadSpread> {
+        let mean = |thread: &(usize, &BenchStats)| thread.1.stats.time.mean;
+
+        let threads = || self.threads.iter().enumerate();
+        let fastest = threads().min_by_key(mean)?;
+        let slowest = threads().max_by_key(mean)?;
+
+        let fastest_picos = mean(&fastest).picos;
+        let slowest_picos = mean(&slowest).picos;
+
+        if fastest_picos == 0 {
+            return None;
+        }
+
+        Some(ThreadSpread {
+            fastest_thread: fastest.0,
+            slowest_thread: slowest.0,
+            ratio: slowest_picos as f64 / fastest_picos as f64,
+        })
+    }
+
+    /// The number of samples taken.
+    #[inline]
+    pub fn sample_count(&self) -> u32 {
+        self.stats.sample_count
+    }
+
+    /// The total number of iterations across all samples.
+    #[inline]
+    pub fn iter_count(&self) -> u64 {
+        self.stats.iter_count
+    }
+
+    /// The number of iterations within each sample.
+    #[inline]
+    pub fn sample_size(&self) -> u32 {
+        self.stats.sample_size
+    }
+
+    /// Total time taken by each sample in nanoseconds, in the order the samples
+    /// were taken.
+    #[inline]
+    pub fn sample_times_nanos(
+        &self,
+    ) -> impl ExactSizeIterator<Item = f64> + '_ {
+        self.stats.sample_durations.iter().map(|d| d.as_nanos_f64())
+    }
+
+    /// Time taken by an iteration.
+    ///
+    /// Durations are truncated to nanoseconds. Use [`BenchStats::time_nanos`]
+    /// for sub-nanosecond precision.
+    #[inline]
+    pub fn time(&self) -> StatsSet<Duration> {
+        self.stats.time.map(Duration::from)
+    }
+
+    /// Time taken by an iteration, in nanoseconds.
+    #[inline]
+    pub fn time_nanos(&self) -> StatsSet<f64> {
+        self.stats.time.map(FineDuration::as_nanos_f64)
+    }
+
+    /// The maximum number of live allocations and bytes.
+    #[inline]
+    pub fn max_alloc(&self) -> AllocTally<StatsSet<f64>> {
+        self.stats.max_alloc
+    }
+
+    /// The net number of allocations and bytes kept at the end of a sample,
+    /// after its inputs and outputs are dropped.
+    ///
+    /// This is negative if iterations free memory allocated before the sample.
+    #[inline]
+    pub fn retained(&self) -> AllocTally<StatsSet<f64>> {
+        self.stats.retained
+    }
+
+    /// The peak process resident set size (RSS) in bytes during the benchmark.
+    ///
+    /// This is `None` unless [`Divan::track_rss`](crate::Divan::track_rss) is
+    /// used on a supported platform.
+    #[inline]
+    pub fn peak_rss(&self) -> Option<u64> {
+        self.stats.peak_rss
+    }
+
+    /// The change in process resident set size (RSS) in bytes from the start
+    /// to the end of the benchmark.
+    ///
+    /// This is `None` unless [`Divan::track_rss`](crate::Divan::track_rss) is
+    /// used on Linux.
+    #[inline]
+    pub fn rss_delta(&self) -> Option<i64> {
+        self.stats.rss_delta
+    }
+
+    /// Tallies of [`GlobalAlloc::alloc`](std::alloc::GlobalAlloc::alloc)
+    /// calls.
+    #[inline]
+    pub fn alloc(&self) -> AllocTally<StatsSet<f64>> {
+        *self.stats.alloc_tallies.get(AllocOp::Alloc)
+    }
+
+    /// Tallies of [`GlobalAlloc::dealloc`](std::alloc::GlobalAlloc::dealloc)
+    /// calls.
+    #[inline]
+    pub fn dealloc(&self) -> AllocTally<StatsSet<f64>> {
+        *self.stats.alloc_tallies.get(AllocOp::Dealloc)
+    }
+
+    /// Tallies of [`GlobalAlloc::realloc`](std::alloc::GlobalAlloc::realloc)
+    /// calls that increased size.
+    #[inline]
+    pub fn grow(&self) -> AllocTally<StatsSet<f64>> {
+        *self.stats.alloc_tallies.get(AllocOp::Grow)
+    }
+
+    /// Tallies of [`GlobalAlloc::realloc`](std::alloc::GlobalAlloc::realloc)
+    /// calls that decreased size.
+    #[inline]
+    pub fn shrink(&self) -> AllocTally<StatsSet<f64>> {
+        *self.stats.alloc_tallies.get(AllocOp::Shrink)
+    }
+
+    /// Tallies of [`GlobalAlloc::alloc_zeroed`](std::alloc::GlobalAlloc::alloc_zeroed)
+    /// calls.
+    ///
+    /// This is zero unless
+    /// [`AllocProfiler::with_alloc_categories`](crate::AllocProfiler::with_alloc_categories)
+    /// is used.
+    #[inline]
+    pub fn zeroed(&self) -> AllocTally<StatsSet<f64>> {
+        *self.stats.alloc_tallies.get(AllocOp::Zeroed)
+    }
+
+    /// Tallies of allocations aligned to more than 16 bytes.
+    ///
+    /// This is zero unless
+    /// [`AllocProfiler::with_alloc_categories`](crate::AllocProfiler::with_alloc_categories)
+    /// is used.
+    #[inline]
+    pub fn aligned(&self) -> AllocTally<StatsSet<f64>> {
+        *self.stats.alloc_tallies.get(AllocOp::Aligned)
+    }
+
+    /// Tallies of allocations and reallocations to sizes at or above the huge
+    /// allocation threshold.
+    ///
+    /// This is zero unless
+    /// [`AllocProfiler::with_alloc_categories`](crate::AllocProfiler::with_alloc_categories)
+    /// is used.
+    #[inline]
+    pub fn huge(&self) -> AllocTally<StatsSet<f64>> {
+        *self.stats.alloc_tallies.get(AllocOp::Huge)
+    }
+
+    /// Distribution of [`BenchStats::max_alloc`] over samples, independent of
+    /// sample times.
+    #[inline]
+    pub fn max_alloc_distribution(&self) -> AllocTally<Distribution<f64>> {
+        self.stats.max_alloc_distribution
+    }
+
+    /// Distribution of [`BenchStats::retained`] over samples, independent of
+    /// sample times.
+    #[inline]
+    pub fn retained_distribution(&self) -> AllocTally<Distribution<f64>> {
+        self.stats.retained_distribution
+    }
+
+    /// Distribution of [`BenchStats::alloc`] over samples, independent of
+    /// sample times.
+    #[inline]
+    pub fn alloc_distribution(&self) -> AllocTally<Distribution<f64>> {
+        *self.stats.alloc_distributions.get(AllocOp::Alloc)
+    }
+
+    /// Distribution of [`BenchStats::dealloc`] over samples, independent of
+    /// sample times.
+    #[inline]
+    pub fn dealloc_distribution(&self) -> AllocTally<Distribution<f64>> {
+        *self.stats.alloc_distributions.get(AllocOp::Dealloc)
+    }
+
+    /// Distribution of [`BenchStats::grow`] over samples, independent of
+    /// sample times.
+    #[inline]
+    pub fn grow_distribution(&self) -> AllocTally<Distribution<f64>> {
+        *self.stats.alloc_distributions.get(AllocOp::Grow)
+    }
+
+    /// Distribution of [`BenchStats::shrink`] over samples, independent of
+    /// sample times.
+    #[inline]
+    pub fn shrink_distribution(&self) -> AllocTally<Distribution<f64>> {
+        *self.stats.alloc_distributions.get(AllocOp::Shrink)
+    }
+
+    /// Distribution of [`BenchStats::zeroed`] over samples, independent of
+    /// sample times.
+    #[inline]
+    pub fn zeroed_distribution(&self) -> AllocTally<Distribution<f64>> {
+        *self.stats.alloc_distributions.get(AllocOp::Zeroed)
+    }
+
+    /// Distribution of [`BenchStats::aligned`] over samples, independent of
+    /// sample times.
+    #[inline]
+    pub fn aligned_distribution(&self) -> AllocTally<Distribution<f64>> {
+        *self.stats.alloc_distributions.get(AllocOp::Aligned)
+    }
+
+    /// Distribution of [`BenchStats::huge`] over samples, independent of
+    /// sample times.
+    #[inline]
+    pub fn huge_distribution(&self) -> AllocTally<Distribution<f64>> {
+        *self.stats.alloc_distributions.get(AllocOp::Huge)
+    }
+
+    /// The mean number of allocations per iteration by size, as
+    /// `(max_size, count)` pairs for each non-empty power-of-two bin.
+    ///
+    /// This is empty unless
+    /// [`AllocProfiler::with_size_histogram`](crate::AllocProfiler::with_size_histogram)
+    /// is used.
+    #[inline]
+    pub fn alloc_sizes(&self) -> impl Iterator<Item = (u64, f64)> + '_ {
+        self.stats.alloc_sizes.non_empty()
+    }
+
+    /// The call sites with the most allocations, as `(call_site, count)` pairs
+    /// in descending order of the estimated number of allocations per
+    /// iteration.
+    ///
+    /// Each call site lists up to 3 frames outside of the standard library,
+    /// one per line, starting with the innermost.
+    ///
+    /// This is empty unless
+    /// [`AllocProfiler::with_call_sites`](crate::AllocProfiler::with_call_sites)
+    /// is used.
+    #[inline]
+    pub fn alloc_call_sites(&self) -> impl Iterator<Item = (&str, f64)> + '_ {
+        self.stats
+            .alloc_call_sites
+            .iter()
+            .map(|(call_site, count)| (call_site.as_str(), *count))
+    }
+
+    /// The number of values processed by an iteration for the given
+    /// [`Counter`](crate::counter::Counter) type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use divan::{counter::BytesCount, report::BenchStats};
+    ///
+    /// fn median_bytes(stats: &BenchStats) -> Option<u64> {
+    ///     stats.counter::<BytesCount>().map(|counts| counts.median as u64)
+    /// }
+    /// ```
+    #[inline]
+    pub fn counter<C: IntoCounter>(&self) -> Option<StatsSet<MaxCountUInt>> {
+        self.stats.get_counts(KnownCounterKind::of::<C>()).copied()
+    }
+
+    /// The number of values processed per second for the given
+    /// [`Counter`](crate::counter::Counter) type.
+    pub fn throughput<C: IntoCounter>(&self) -> Option<StatsSet<f64>> {
+        self.throughput_of(KnownCounterKind::of::<C>())
+    }
+
+    pub(crate) fn throughput_of(
+        &self,
+        counter_kind: KnownCounterKind,
+    ) -> Option<StatsSet<f64>> {
+        let counts = self.stats.get_counts(counter_kind)?;
+        let time = &self.stats.time;
+
+        let per_sec = |count: MaxCountUInt, time: FineDuration| -> f64 {
+            if count == 0 {
+                0.0
+            } else {
+                count as f64 * (1e12 / time.picos as f64)
+            }
+        };
+
+        Some(StatsSet {
+            fastest: per_sec(counts.fastest, time.fastest),
+            slowest: per_sec(counts.slowest, time.slowest),
+            median: per_sec(counts.median, time.median),
+            mean: per_sec(counts.mean, time.mean),
+        })
+    }
+}
+
+/// How evenly time was spread across the threads of a benchmark, returned by
+/// [`BenchStats::thread_spread`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ThreadSpread {
+    pub(crate) fastest_thread: usize,
+    pub(crate) slowest_thread: usize,
+    pub(crate) ratio: f64,
+}
+
+impl ThreadSpread {
+    /// The index of the thread with the lowest mean time.
+    #[inline]
+    pub fn fastest_thread(&self) -> usize {
+        self.fastest_thread
+    }
+
+    /// The index of the thread with the highest mean time.
+    #[inline]
+    pub fn slowest_thread(&self) -> usize {
+        self.slowest_thread
+    }
+
+    /// The slowest thread's mean time divided by the fastest thread's.
+    #[inline]
+    pub fn ratio(&self) -> f64 {
+        self.ratio
+    }
+}
+
+/// Measurement statistics for the threads of a single
+/// [`ThreadRole`](crate::ThreadRole).
+#[derive(Clone, Debug)]
+pub struct RoleStats {
+    pub(crate) name: String,
+    pub(crate) thread_count: usize,
+    pub(crate) stats: BenchStats,
+}
+
+impl RoleStats {
+    /// The name of the role.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The number of threads that ran as this role.
+    #[inline]
+    pub fn thread_count(&self) -> usize {
+        self.thread_count
+    }
+
+    /// Statistics over samples taken by threads of this role.
+    #[inline]
+    pub fn stats(&self) -> &BenchStats {
+        &self.stats
+    }
+}
+
+/// Receives benchmark events as they happen.
+///
+/// Reporters are registered with [`Divan::reporter`](crate::Divan::reporter).
+/// All methods do nothing by default, so implementations only need to handle
+/// the events they care about.
+///
+/// The tree printed to the terminal is also produced by a reporter.
+///
+/// # Examples
+///
+/// ```
+/// use divan::{
+///     report::{BenchInfo, BenchStats, Reporter},
+///     Divan,
+/// };
+///
+/// struct MetricsReporter;
+///
+/// impl Reporter for MetricsReporter {
+///     fn finish_bench(&mut self, bench: &BenchInfo, stats: Option<&BenchStats>) {
+///         if let Some(stats) = stats {
+///             println!("{} = {:?}", bench.path(), stats.time().median);
+///         }
+///     }
+/// }
+///
+/// let divan = Divan::default().reporter(MetricsReporter);
+/// ```
+pub trait Reporter {
+    /// Called when entering a module, group, or benchmark with multiple runs.
+    #[inline]
+    fn start_group(&mut self, group: &GroupInfo) {
+        _ = group;
+    }
+
+    /// Called after all children of a group have been run.
+    #[inline]
+    fn finish_group(&mut self, group: &GroupInfo) {
+        _ = group;
+    }
+
+    /// Called before a benchmark is run.
+    #[inline]
+    fn start_bench(&mut self, bench: &BenchInfo) {
+        _ = bench;
+    }
+
+    /// Called after a benchmark is run.
+    ///
+    /// `stats` is `None` if the benchmark was not measured, such as when run
+    /// with `--test` or `--list`.
+    #[inline]
+    fn finish_bench(&mut self, bench: &BenchInfo, stats: Option<&BenchStats>) {
+        _ = (bench, stats);
+    }
+
+    /// Called instead of [`start_bench`](Self::start_bench) if the benchmark
+    /// is skipped for being marked [`ignore`](macro@crate::bench#ignore).
+    #[inline]
+    fn ignore_bench(&mut self, bench: &BenchInfo) {
+        _ = bench;
+    }
+
+    /// Called instead of [`finish_bench`](Self::finish_bench) if the
+    /// benchmark panicked.
+    ///
+    /// The panic is resumed after all reporters are notified, unless running
+    /// with [`Divan::keep_going`](crate::Divan::keep_going).
+    #[inline]
+    fn bench_panicked(&mut self, bench: &BenchInfo, message: &str) {
+        _ = (bench, message);
+    }
+
+    /// Called instead of [`finish_bench`](Self::finish_bench) if the
+    /// benchmark function returned an error.
+    ///
+    /// `message` is the error formatted with [`Display`](std::fmt::Display).
+    /// The benchmark is skipped and the run continues.
+    #[inline]
+    fn bench_failed(&mut self, bench: &BenchInfo, message: &str) {
+        _ = (bench, message);
+    }
+}
+
+impl<R: Reporter + ?Sized> Reporter for &mut R {
+    #[inline]
+    fn start_group(&mut self, group: &GroupInfo) {
+        R::start_group(self, group);
+    }
+
+    #[inline]
+    fn finish_group(&mut self, group: &GroupInfo) {
+        R::finish_group(self, group);
+    }
+
+    #[inline]
+    fn start_bench(&mut self, bench: &BenchInfo) {
+        R::start_bench(self, bench);
+    }
+
+    #[inline]
+    fn finish_bench(&mut self, bench: &BenchInfo, stats: Option<&BenchStats>) {
+        R::finish_bench(self, bench, stats);
+    }
+
+    #[inline]
+    fn ignore_bench(&mut self, bench: &BenchInfo) {
+        R::ignore_bench(self, bench);
+    }
+
+    #[inline]
+    fn bench_panicked(&mut self, bench: &BenchInfo, message: &str) {
+        R::bench_panicked(self, bench, message);
+    }
+
+    #[inline]
+    fn bench_failed(&mut self, bench: &BenchInfo, message: &str) {
+        R::bench_failed(self, bench, message);
+    }
+}
+
+impl<R: Reporter> Reporter for [R] {
+    fn start_group(&mut self, group: &GroupInfo) {
+        self.iter_mut().for_each(|r| r.start_group(group));
+    }
+
+    fn finish_group(&mut self, group: &GroupInfo) {
+        self.iter_mut().for_each(|r| r.finish_group(group));
+    }
+
+    fn start_bench(&mut self, bench: &BenchInfo) {
+        self.iter_mut().for_each(|r| r.start_bench(bench));
+    }
+
+    fn finish_bench(&mut self, bench: &BenchInfo, stats: Option<&BenchStats>) {
+        self.iter_mut().for_each(|r| r.finish_bench(bench, stats));
+    }
+
+    fn ignore_bench(&mut self, bench: &BenchInfo) {
+        self.iter_mut().for_each(|r| r.ignore_bench(bench));
+    }
+
+    fn bench_panicked(&mut self, bench: &BenchInfo, message: &str) {
+        self.iter_mut().for_each(|r| r.bench_panicked(bench, message));
+    }
+
+    fn bench_failed(&mut self, bench: &BenchInfo, message: &str) {
+        self.iter_mut().for_each(|r| r.bench_failed(bench, message));
+    }
+}
+
+/// Group node passed to [`Reporter`] methods.
+#[derive(Clone, Copy, Debug)]
+pub struct GroupInfo<'a> {
+    pub(crate) name: &'a str,
+    pub(crate) path: &'a str,
+    pub(crate) is_last: bool,
+    pub(crate) complexity: GroupComplexity,
+    pub(crate) thread_scaling: &'a [ThreadScaling],
+}
+
+impl<'a> GroupInfo<'a> {
+    #[inline]
+    pub(crate) fn new(name: &'a str, path: &'a str, is_last: bool) -> Self {
+        Self {
+            name,
+            path,
+            is_last,
+            complexity: GroupComplexity::default(),
+            thread_scaling: &[],
+        }
+    }
+
+    /// The name displayed for this group.
+    #[inline]
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    /// The `::`-joined names from the root of the tree to this group.
+    #[inline]
+    pub fn path(&self) -> &'a str {
+        self.path
+    }
+
+    /// Whether this is the last child of its parent.
+    #[inline]
+    pub fn is_last(&self) -> bool {
+        self.is_last
+    }
+
+    /// See [`GroupReport::time_complexity`].
+    ///
+    /// This is only available in [`Reporter::finish_group`].
+    #[inline]
+    pub fn time_complexity(&self) -> Option<ComplexityFit> {
+        self.complexity.time
+    }
+
+    /// See [`GroupReport::alloc_complexity`].
+    ///
+    /// This is only available in [`Reporter::finish_group`].
+    #[inline]
+    pub fn alloc_complexity(&self) -> Option<ComplexityFit> {
+        self.complexity.alloc
+    }
+
+    /// See [`GroupReport::thread_scaling`].
+    ///
+    /// This is only available in [`Reporter::finish_group`].
+    #[inline]
+    pub fn thread_scaling(&self) -> &'a [ThreadScaling] {
+        self.thread_scaling
+    }
+}
+
+/// Benchmark run passed to [`Reporter`] methods.
+#[derive(Clone, Copy, Debug)]
+pub struct BenchInfo<'a> {
+    pub(crate) name: &'a str,
+    pub(crate) path: &'a str,
+    pub(crate) is_last: bool,
+    pub(crate) thread_count: usize,
+    pub(crate) pinned_cpus: &'a [usize],
+    pub(crate) options: &'a BenchOptions<'a>,
+}
+
+impl<'a> BenchInfo<'a> {
+    /// The name displayed for this benchmark.
+    #[inline]
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    /// The `::`-joined names from the root of the tree to this benchmark.
+    #[inline]
+    pub fn path(&self) -> &'a str {
+        self.path
+    }
+
+    /// Whether this is the last child of its parent.
+    #[inline]
+    pub fn is_last(&self) -> bool {
+        self.is_last
+    }
+
+    /// The number of threads the benchmark is run on.
+    #[inline]
+    pub fn thread_count(&self) -> usize {
+        self.thread_count
+    }
+
+    /// The CPU each thread was pinned to, by thread index, where thread 0 is
+    /// the main thread.
+    ///
+    /// This is empty if threads are not [pinned](macro@crate::bench#pin).
+    /// Pinning is applied while the benchmark runs, so this is also empty in
+    /// [`Reporter::start_bench`].
+    #[inline]
+    pub fn pinned_cpus(&self) -> &'a [usize] {
+        self.pinned_cpus
+    }
+
+    /// The options used to run this benchmark, after combining options from
+    /// parent groups and [`Divan`](crate::Divan).
+    #[inline]
+    pub fn options(&self) -> OptionsInfo<'a> {
+        OptionsInfo { options: self.options }
+    }
+}
+
+/// Options used to run a benchmark, set by
+/// [`#[divan::bench]`](macro@crate::bench),
+/// [`#[divan::bench_group]`](macro@crate::bench_group), or
+/// [`Divan`](crate::Divan).
+///
+/// Each option is `None` if not set, in which case Divan uses its default.
+#[derive(Clone, Copy, Debug)]
+pub struct OptionsInfo<'a> {
+    options: &'a BenchOptions<'a>,
+}
+
+impl<'a> OptionsInfo<'a> {
+    /// The number of sample recordings.
+    #[inline]
+    pub fn sample_count(&self) -> Option<u32> {
+        self.options.sample_count
+    }
+
+    /// The number of iterations inside a single sample.
+    #[inline]
+    pub fn sample_size(&self) -> Option<u32> {
+        self.options.sample_size
+    }
+
+    /// The thread counts to run the benchmark on, where 0 means
+    /// [available parallelism](std::thread::available_parallelism).
+    #[inline]
+    pub fn threads(&self) -> Option<&'a [usize]> {
+        self.options.threads.as_deref()
+    }
+
+    /// How benchmarking threads are pinned to CPUs.
+    #[inline]
+    pub fn pin(&self) -> Option<&'a CpuPin> {
+        self.options.pin.as_ref()
+    }
+
+    /// The time floor for benchmarking a function.
+    #[inline]
+    pub fn min_time(&self) -> Option<Duration> {
+        self.options.min_time
+    }
+
+    /// The time ceiling for benchmarking a function.
+    #[inline]
+    pub fn max_time(&self) -> Option<Duration> {
+        self.options.max_time
+    }
+
+    /// Whether time external to benchmarked functions is skipped when
+    /// accounting for [`min_time`](Self::min_time) or
+    /// [`max_time`](Self::max_time).
+    #[inline]
+    pub fn skip_ext_time(&self) -> Option<bool> {
+        self.options.skip_ext_time
+    }
+
+    /// The maximum number of allocations allowed per iteration.
+    #[inline]
+    pub fn max_allocs(&self) -> Option<u64> {
+        self.options.max_allocs
+    }
+
+    /// The maximum number of bytes allowed to be retained per iteration.
+    #[inline]
+    pub fn max_retained_bytes(&self) -> Option<u64> {
+        self.options.max_retained_bytes
+    }
+
+    /// Whether [`AllocProfiler`](crate::AllocProfiler) tallies allocations.
+    #[inline]
+    pub fn alloc_profile(&self) -> Option<bool> {
+        self.options.alloc_profile
+    }
+
+    /// Whether the benchmark is marked [`ignore`](macro@crate::bench#ignore).
+    #[inline]
+    pub fn ignore(&self) -> Option<bool> {
+        self.options.ignore
+    }
+}
+
+/// Incrementally builds a [`Report`] while the benchmark tree is run.
+#[derive(Default)]
+pub(crate) struct ReportBuilder {
+    /// Groups that have been started but not finished.
+    stack: Vec<GroupReport>,
+
+    /// Finished top-level nodes.
+    nodes: Vec<ReportNode>,
+}
+
+impl ReportBuilder {
+    fn push(&mut self, node: ReportNode) {
+        match self.stack.last_mut() {
+            Some(parent) => parent.children.push(node),
+            None => self.nodes.push(node),
+        }
+    }
+
+    fn push_bench(
+        &mut self,
+        bench: &BenchInfo,
+        ignored: bool,
+        error: Option<&str>,
+        panic_message: Option<&str>,
+        stats: Option<&BenchStats>,
+    ) {
+        self.push(ReportNode::Bench(BenchReport {
+            name: bench.name.to_owned(),
+            path: bench.path.to_owned(),
+            thread_count: bench.thread_count,
+            pinned_cpus: bench.pinned_cpus.to_vec(),
+            options: bench.options.clone().into_owned(),
+            ignored,
+            error: error.map(str::to_owned),
+            panic_message: panic_message.map(str::to_owned),
+            stats: stats.cloned(),
+        }));
+    }
+
+    pub fn finish(mut self, timer: Option<Timer>) -> Report {
+        while let Some(group) = self.stack.pop() {
+            self.push(ReportNode::Group(group));
+        }
+
+        Report { timer: timer.map(TimerInfo::new), nodes: self.nodes }
+    }
+}
+
+impl Reporter for ReportBuilder {
+    fn start_group(&mut self, group: &GroupInfo) {
+        self.stack.push(GroupReport {
+            name: group.name.to_owned(),
+            path: group.path.to_owned(),
+            children: Vec::new(),
+            complexity: GroupComplexity::default(),
+            thread_scaling: Vec::new(),
+        });
+    }
+
+    fn finish_group(&mut self, group_info: &GroupInfo) {
+        if let Some(mut group) = self.stack.pop() {
+            group.complexity = group_info.complexity;
+            group.thread_scaling = group_info.thread_scaling.to_vec();
+            self.push(ReportNode::Group(group));
+        }
+    }
+
+    fn finish_bench(&mut self, bench: &BenchInfo, stats: Option<&BenchStats>) {
+        self.push_bench(bench, false, None, None, stats);
+    }
+
+    fn ignore_bench(&mut self, bench: &BenchInfo) {
+        self.push_bench(bench, true, None, None, None);
+    }
+
+    fn bench_panicked(&mut self, bench: &BenchInfo, message: &str) {
+        self.push_bench(bench, false, None, Some(message), None);
+    }
+
+    fn bench_failed(&mut self, bench: &BenchInfo, message: &str) {
+        self.push_bench(bench, false, Some(message), None, None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        stats::{Stats, StatsSet},
+        time::FineDuration,
+    };
+
+    /// Returns stats with a mean iteration time of `nanos`.
+    fn stats(nanos: u64, threads: Vec<BenchStats>) -> BenchStats {
+        let mean = FineDuration { picos: nanos as u128 * 1_000 };
+
+        BenchStats {
+            stats: Stats {
+                time: StatsSet { mean, ..StatsSet::default() },
+                ..Stats::default()
+            },
+            roles: Vec::new(),
+            threads,
+        }
+    }
+
+    #[test]
+    fn thread_spread() {
+        let spread = stats(0, vec![stats(20, vec![]), stats(10, vec![])])
+            .thread_spread()
+            .unwrap();
+        assert_eq!(spread.fastest_thread, 1);
+        assert_eq!(spread.slowest_thread, 0);
+        assert_eq!(spread.ratio, 2.0);
+
+        assert!(stats(10, vec![]).thread_spread().is_none());
+
+        // There is no ratio to a fastest time of 0.
+        let zero = stats(0, vec![stats(0, vec![]), stats(10, vec![])]);
+        assert!(zero.thread_spread().is_none());
+    }
+}
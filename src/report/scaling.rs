@@ -0,0 +1,174 @@
+use crate::{
+    counter::{AnyCounter, BytesFormat, IntoCounter, KnownCounterKind},
+    report::BenchStats,
+};
+
+/// How a benchmark run over multiple [`threads`](macro@crate::bench#threads)
+/// scales relative to running on a single thread.
+///
+/// Each thread runs the benchmarked function in a loop, so the work done by
+/// `N` threads is `N` times the iterations of each thread. Scaling is based on
+/// median iteration time.
+///
+/// This is only computed for benchmarks that run the same function on every
+/// thread, so not for [`Bencher::bench_custom`](crate::Bencher::bench_custom),
+/// [`Bencher::bench_roles`](crate::Bencher::bench_roles), or `bench_local`
+/// methods.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ThreadScaling {
+    pub(crate) thread_count: usize,
+    pub(crate) speedup: Option<f64>,
+
+    /// Aggregate number of values processed per second by all threads.
+    pub(crate) throughput: [Option<f64>; KnownCounterKind::COUNT],
+}
+
+impl ThreadScaling {
+    /// Computes scaling of each run relative to the run on 1 thread.
+    ///
+    /// Returns nothing if there is no run on 1 thread or no other run to
+    /// compare it to.
+    pub(crate) fn compute(runs: &[(usize, &BenchStats)]) -> Vec<Self> {
+        let Some(&(_, single)) =
+            runs.iter().find(|&&(thread_count, _)| thread_count == 1)
+        else {
+            return Vec::new();
+        };
+
+        if runs.len() < 2 {
+            return Vec::new();
+        }
+
+        let single_picos = single.stats.time.median.picos;
+
+        runs.iter()
+            .map(|&(thread_count, stats)| {
+                let picos = stats.stats.time.median.picos;
+
+                Self {
+                    thread_count,
+
+                    // Times too fast to measure give no basis for comparison.
+                    speedup: if picos == 0 || single_picos == 0 {
+                        None
+                    } else {
+                        Some(
+                            thread_count as f64 * single_picos as f64
+                                / picos as f64,
+                        )
+                    },
+                    throughput: KnownCounterKind::ALL.map(|counter_kind| {
+                        let throughput = stats.throughput_of(counter_kind)?;
+                        Some(throughput.median * thread_count as f64)
+                    }),
+                }
+            })
+            .collect()
+    }
+
+    /// The number of threads the benchmark was run on.
+    #[inline]
+    pub fn thread_count(&self) -> usize {
+        self.thread_count
+    }
+
+    /// The total rate of iterations across all threads, relative to 1 thread.
+    ///
+    /// Returns `None` if either median time is 0, such as when it is below
+    /// timer precision.
+    #[inline]
+    pub fn speedup(&self) -> Option<f64> {
+        self.speedup
+    }
+
+    /// [`speedup`](Self::speedup) divided by the number of threads, where 1
+    /// means perfect scaling.
+    #[inline]
+    pub fn efficiency(&self) -> Option<f64> {
+        Some(self.speedup? / self.thread_count as f64)
+    }
+
+    /// The number of values processed per second by all threads for the given
+    /// [`Counter`](crate::counter::Counter) type.
+    #[inline]
+    pub fn throughput<C: IntoCounter>(&self) -> Option<f64> {
+        self.throughput[KnownCounterKind::of::<C>() as usize]
+    }
+
+    /// Formats aggregate throughput for each counter, such as `1.2 GB/s`.
+    pub(crate) fn display_throughputs(
+        &self,
+        bytes_format: BytesFormat,
+    ) -> impl Iterator<Item = String> + '_ {
+        KnownCounterKind::ALL.into_iter().filter_map(move |counter_kind| {
+            let per_sec = self.throughput[counter_kind as usize]?;
+
+            // Display the count per second as the count per 1 second.
+            let counter = AnyCounter::known(counter_kind, per_sec as _);
+            let second = crate::time::FineDuration { picos: 1_000_000_000_000 };
+
+            Some(counter.display_throughput(second, bytes_format).to_string())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        stats::{Stats, StatsSet},
+        time::FineDuration,
+    };
+
+    /// Returns stats with a median iteration time of `nanos`.
+    fn stats(nanos: u64) -> BenchStats {
+        let median = FineDuration { picos: nanos as u128 * 1_000 };
+
+        BenchStats {
+            stats: Stats {
+                time: StatsSet { median, ..StatsSet::default() },
+                ..Stats::default()
+            },
+            roles: Vec::new(),
+            threads: Vec::new(),
+        }
+    }
+
+    fn speedups(runs: &[(usize, &BenchStats)]) -> Vec<(usize, Option<f64>)> {
+        ThreadScaling::compute(runs)
+            .iter()
+            .map(|scaling| (scaling.thread_count(), scaling.speedup()))
+            .collect()
+    }
+
+    #[test]
+    fn speedup() {
+        let [ten, twenty] = [stats(10), stats(20)];
+
+        assert_eq!(
+            speedups(&[(1, &ten), (2, &ten), (4, &twenty)]),
+            [(1, Some(1.0)), (2, Some(2.0)), (4, Some(2.0))]
+        );
+
+        // Scaling is relative to 1 thread.
+        assert_eq!(speedups(&[(2, &ten), (4, &twenty)]), []);
+
+        // There is nothing to compare a single run to.
+        assert_eq!(speedups(&[(1, &ten)]), []);
+    }
+
+    #[test]
+    fn zero_median() {
+        let [zero, ten] = [stats(0), stats(10)];
+
+        assert_eq!(
+            speedups(&[(1, &ten), (2, &zero)]),
+            [(1, Some(1.0)), (2, None)]
+        );
+
+        assert_eq!(speedups(&[(1, &zero), (2, &ten)]), [(1, None), (2, None)]);
+
+        let scaling = ThreadScaling::compute(&[(1, &ten), (2, &zero)]);
+        assert_eq!(scaling[1].efficiency(), None);
+    }
+}
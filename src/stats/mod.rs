@@ -1,9 +1,10 @@
 //! Measurement statistics.
 
 use crate::{
-    alloc::{AllocOpMap, AllocTally},
+    alloc::{AllocOpMap, AllocSizeBins, AllocTally},
     counter::{KnownCounterKind, MaxCountUInt},
     time::FineDuration,
+    util,
 };
 
 mod sample;
@@ -11,13 +12,20 @@ mod sample;
 pub(crate) use sample::*;
 
 /// Statistics from samples.
+#[derive(Clone, Debug, Default)]
 pub(crate) struct Stats {
     /// Total number of samples taken.
     pub sample_count: u32,
 
+    /// The number of iterations within each sample.
+    pub sample_size: u32,
+
     /// Total number of iterations (currently `sample_count * `sample_size`).
     pub iter_count: u64,
 
+    /// Total time taken by each sample, in the order they were taken.
+    pub sample_durations: Vec<FineDuration>,
+
     /// Timing statistics.
     pub time: StatsSet<FineDuration>,
 
@@ -25,10 +33,36 @@ pub(crate) struct Stats {
     /// with the corresponding samples for `time`.
     pub max_alloc: AllocTally<StatsSet<f64>>,
 
+    /// Net allocated bytes and number of allocations kept at the end of each
+    /// sample, associated with the corresponding samples for `time`.
+    pub retained: AllocTally<StatsSet<f64>>,
+
     /// Allocation statistics associated with the corresponding samples for
     /// `time`.
     pub alloc_tallies: AllocOpMap<AllocTally<StatsSet<f64>>>,
 
+    /// Distribution of `max_alloc` over samples, independent of `time`.
+    pub max_alloc_distribution: AllocTally<Distribution<f64>>,
+
+    /// Distribution of `retained` over samples, independent of `time`.
+    pub retained_distribution: AllocTally<Distribution<f64>>,
+
+    /// Distributions of `alloc_tallies` over samples, independent of `time`.
+    pub alloc_distributions: AllocOpMap<AllocTally<Distribution<f64>>>,
+
+    /// Call sites with the most allocations per iteration, in descending
+    /// order.
+    pub alloc_call_sites: Vec<(String, f64)>,
+
+    /// Mean number of allocations per iteration in each size bin.
+    pub alloc_sizes: AllocSizeBins<f64>,
+
+    /// Peak process resident set size (RSS) in bytes, if measured.
+    pub peak_rss: Option<u64>,
+
+    /// Change in process RSS in bytes across the benchmark, if measured.
+    pub rss_delta: Option<i64>,
+
     /// `Counter` counts associated with the corresponding samples for `time`.
     pub counts: [Option<StatsSet<MaxCountUInt>>; KnownCounterKind::COUNT],
 }
@@ -42,8 +76,10 @@ impl Stats {
     }
 }
 
-#[derive(Debug)]
-pub(crate) struct StatsSet<T> {
+/// Statistics over iterations, associated with the time taken by each
+/// iteration.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StatsSet<T> {
     /// Associated with minimum amount of time taken by an iteration.
     pub fastest: T,
 
@@ -57,11 +93,101 @@ pub(crate) struct StatsSet<T> {
     pub mean: T,
 }
 
+impl<T> StatsSet<T> {
+    /// Applies `f` to each statistic.
+    #[inline]
+    pub fn map<U>(self, mut f: impl FnMut(T) -> U) -> StatsSet<U> {
+        StatsSet {
+            fastest: f(self.fastest),
+            slowest: f(self.slowest),
+            median: f(self.median),
+            mean: f(self.mean),
+        }
+    }
+}
+
 impl StatsSet<f64> {
-    pub fn is_zero(&self) -> bool {
+    pub(crate) fn is_zero(&self) -> bool {
         self.fastest == 0.0
             && self.slowest == 0.0
             && self.median == 0.0
             && self.mean == 0.0
     }
 }
+
+/// Distribution of a statistic over samples, independent of the time taken by
+/// each sample.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Distribution<T> {
+    /// The smallest value of any sample.
+    pub min: T,
+
+    /// The largest value of any sample.
+    pub max: T,
+
+    /// The midpoint value of all samples.
+    pub median: T,
+
+    /// The average value of all samples.
+    pub mean: T,
+}
+
+impl<T> Distribution<T> {
+    /// Applies `f` to each statistic.
+    #[inline]
+    pub fn map<U>(self, mut f: impl FnMut(T) -> U) -> Distribution<U> {
+        Distribution {
+            min: f(self.min),
+            max: f(self.max),
+            median: f(self.median),
+            mean: f(self.mean),
+        }
+    }
+
+    /// Places `min` and `max` in the `fastest` and `slowest` positions for
+    /// display in time-based columns.
+    #[inline]
+    pub(crate) fn into_stats_set(self) -> StatsSet<T> {
+        StatsSet {
+            fastest: self.min,
+            slowest: self.max,
+            median: self.median,
+            mean: self.mean,
+        }
+    }
+}
+
+impl Distribution<f64> {
+    /// Computes the distribution of `values`, sorting them in place.
+    ///
+    /// If there are an even number of values, the median is the average of
+    /// the two middle values.
+    pub(crate) fn from_values(values: &mut [f64]) -> Self {
+        values.sort_unstable_by(f64::total_cmp);
+
+        let average = |values: &[f64]| -> f64 {
+            if values.is_empty() {
+                0.0
+            } else {
+                values.iter().sum::<f64>() / values.len() as f64
+            }
+        };
+
+        Self {
+            min: values.first().copied().unwrap_or_default(),
+            max: values.last().copied().unwrap_or_default(),
+            median: average(util::slice_middle(values)),
+            mean: average(values),
+        }
+    }
+}
+
+impl<T> AllocTally<Distribution<T>> {
+    #[inline]
+    pub(crate) fn into_stats_set(self) -> AllocTally<StatsSet<T>> {
+        AllocTally {
+            count: self.count.into_stats_set(),
+            size: self.size.into_stats_set(),
+        }
+    }
+}
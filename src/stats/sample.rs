@@ -24,6 +24,7 @@ pub(crate) struct RawSample {
     pub end: Timestamp,
     pub timer: Timer,
     pub alloc_info: ThreadAllocInfo,
+    pub call_sites: Vec<(String, u64)>,
     pub counter_totals: [u128; KnownCounterKind::COUNT],
 }
 
@@ -46,6 +47,9 @@ pub(crate) struct SampleCollection {
 
     /// Allocation information associated with `time_samples` by index.
     pub alloc_info_by_sample: HashMap<u32, ThreadAllocInfo>,
+
+    /// Estimated number of allocations by call site across all samples.
+    pub alloc_call_sites: HashMap<String, u64>,
 }
 
 impl SampleCollection {
@@ -54,6 +58,7 @@ impl SampleCollection {
     pub fn clear(&mut self) {
         self.time_samples.clear();
         self.alloc_info_by_sample.clear();
+        self.alloc_call_sites.clear();
     }
 
     /// Computes the total number of iterations across all samples.
@@ -72,6 +77,39 @@ impl SampleCollection {
         }
     }
 
+    /// Returns the samples recorded by threads matching `keep`.
+    ///
+    /// Samples are recorded by each of `thread_count` threads in turn, so the
+    /// thread of a sample is its index modulo `thread_count`. Call sites are
+    /// not kept because they are not recorded per thread.
+    pub fn filter_threads(
+        &self,
+        thread_count: usize,
+        keep: impl Fn(usize) -> bool,
+    ) -> Self {
+        let mut result =
+            Self { sample_size: self.sample_size, ..Self::default() };
+
+        for (index, sample) in self.time_samples.iter().enumerate() {
+            if !keep(index % thread_count) {
+                continue;
+            }
+
+            if let Some(alloc_info) =
+                self.alloc_info_by_sample.get(&(index as u32))
+            {
+                result.alloc_info_by_sample.insert(
+                    result.time_samples.len() as u32,
+                    alloc_info.clone(),
+                );
+            }
+
+            result.time_samples.push(TimeSample { duration: sample.duration });
+        }
+
+        result
+    }
+
     /// Returns all samples sorted by duration.
     #[inline]
     pub fn sorted_samples(&self) -> Vec<&TimeSample> {
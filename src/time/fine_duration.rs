@@ -20,6 +20,20 @@ impl From<Duration> for FineDuration {
     }
 }
 
+impl From<FineDuration> for Duration {
+    /// Converts to a `Duration`, truncating to nanoseconds and saturating to
+    /// `Duration::MAX`.
+    #[inline]
+    fn from(duration: FineDuration) -> Self {
+        let nanos = duration.picos / 1_000;
+
+        match u64::try_from(nanos / 1_000_000_000) {
+            Ok(secs) => Duration::new(secs, (nanos % 1_000_000_000) as u32),
+            Err(_) => Duration::MAX,
+        }
+    }
+}
+
 impl fmt::Display for FineDuration {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let sig_figs = f.precision().unwrap_or(4);
@@ -116,6 +130,12 @@ impl FineDuration {
         self.picos == 0
     }
 
+    /// Returns the duration in nanoseconds as a floating point number.
+    #[inline]
+    pub fn as_nanos_f64(self) -> f64 {
+        self.picos as f64 / 1_000.0
+    }
+
     /// Round up to `other` if `self` is zero.
     #[inline]
     pub fn clamp_to(self, other: Self) -> Self {
@@ -7,7 +7,7 @@ use crate::{
 };
 
 /// Measures time.
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, Debug, Default)]
 pub(crate) enum Timer {
     /// Operating system timer.
     #[default]
@@ -3,9 +3,14 @@
 use std::{io::Write, iter::repeat};
 
 use crate::{
-    alloc::{AllocOp, AllocTally},
+    alloc::{AllocOp, AllocOpMap, AllocTally},
     counter::{AnyCounter, BytesFormat, KnownCounterKind},
+    report::{
+        BenchInfo, BenchStats, BigO, ComplexityFit, GroupInfo, Reporter,
+        ThreadScaling,
+    },
     stats::{Stats, StatsSet},
+    time::FineDuration,
     util,
 };
 
@@ -19,6 +24,12 @@ pub(crate) struct TreePainter {
 
     column_widths: [usize; TreeColumn::COUNT],
 
+    bytes_format: BytesFormat,
+
+    /// Whether to write allocation distributions instead of allocations of
+    /// the fastest, slowest, and median samples.
+    alloc_distribution: bool,
+
     depth: usize,
 
     /// The current prefix to the name and content, e.g.
@@ -34,10 +45,14 @@ impl TreePainter {
     pub fn new(
         max_name_span: usize,
         column_widths: [usize; TreeColumn::COUNT],
+        bytes_format: BytesFormat,
+        alloc_distribution: bool,
     ) -> Self {
         Self {
             max_name_span,
             column_widths,
+            bytes_format,
+            alloc_distribution,
             depth: 0,
             current_prefix: String::new(),
             write_buf: String::new(),
@@ -50,6 +65,7 @@ impl TreePainter {
     pub fn start_parent(&mut self, name: &str, is_last: bool) {
         let is_top_level = self.depth == 0;
         let has_columns = self.has_columns();
+        let alloc_distribution = self.alloc_distribution;
 
         let buf = &mut self.write_buf;
         buf.clear();
@@ -77,7 +93,9 @@ impl TreePainter {
 
         // Write column headings.
         if has_columns && is_top_level {
-            let names = TreeColumnData::from_fn(TreeColumn::name);
+            let names = TreeColumnData::from_fn(|column| {
+                column.name(alloc_distribution)
+            });
             names.write(buf, &mut self.column_widths);
         }
 
@@ -173,13 +191,25 @@ impl TreePainter {
         println!();
     }
 
+    /// Exit the current leaf node, emitting the error or panic that failed
+    /// it.
+    pub fn fail_leaf(&mut self, label: &str, message: &str) {
+        // The leaf name is only padded if there are columns.
+        let pad = if self.has_columns() { "" } else { "  " };
+
+        println!("{pad}{label}: {message}");
+    }
+
     /// Exit the current leaf node, emitting statistics.
     pub fn finish_leaf(
         &mut self,
         is_last: bool,
-        stats: &Stats,
-        bytes_format: BytesFormat,
+        bench_stats: &BenchStats,
+        pinned_cpus: &[usize],
     ) {
+        let stats = &bench_stats.stats;
+        let bytes_format = self.bytes_format;
+
         let prep_buffer = |buf: &mut String, max_span: &mut usize| {
             buf.clear();
             buf.push_str(&self.current_prefix);
@@ -194,14 +224,30 @@ impl TreePainter {
         let buf = &mut self.write_buf;
         buf.clear();
 
+        // Allocation statistics to write, with distribution minimums and
+        // maximums in the min and max columns.
+        let (max_alloc, retained, alloc_tallies) = if self.alloc_distribution {
+            (
+                stats.max_alloc_distribution.into_stats_set(),
+                stats.retained_distribution.into_stats_set(),
+                AllocOpMap {
+                    values: stats
+                        .alloc_distributions
+                        .values
+                        .map(AllocTally::into_stats_set),
+                },
+            )
+        } else {
+            (stats.max_alloc, stats.retained, stats.alloc_tallies)
+        };
+
         // Serialize max alloc counts and sizes early so we can resize columns
         // early.
-        let serialized_max_alloc_counts = if stats.max_alloc.size.is_zero() {
+        let serialized_max_alloc_counts = if max_alloc.size.is_zero() {
             None
         } else {
             Some(TreeColumn::ALL.map(|column| {
-                let Some(&max_alloc_count) =
-                    column.get_stat(&stats.max_alloc.count)
+                let Some(&max_alloc_count) = column.get_stat(&max_alloc.count)
                 else {
                     return String::new();
                 };
@@ -211,12 +257,11 @@ impl TreePainter {
             }))
         };
 
-        let serialized_max_alloc_sizes = if stats.max_alloc.size.is_zero() {
+        let serialized_max_alloc_sizes = if max_alloc.size.is_zero() {
             None
         } else {
             Some(TreeColumn::ALL.map(|column| {
-                let Some(&max_alloc_size) =
-                    column.get_stat(&stats.max_alloc.size)
+                let Some(&max_alloc_size) = column.get_stat(&max_alloc.size)
                 else {
                     return String::new();
                 };
@@ -230,9 +275,7 @@ impl TreePainter {
         };
 
         // Serialize alloc tallies early so we can resize columns early.
-        let serialized_alloc_tallies = AllocOp::ALL.map(|op| {
-            let tally = stats.alloc_tallies.get(op);
-
+        let serialize_alloc_tally = |tally: &AllocTally<StatsSet<f64>>| {
             if tally.is_zero() {
                 return None;
             }
@@ -274,7 +317,12 @@ impl TreePainter {
                     }
                 }),
             })
-        });
+        };
+
+        let serialized_retained = serialize_alloc_tally(&retained);
+
+        let serialized_alloc_tallies =
+            AllocOp::ALL.map(|op| serialize_alloc_tally(alloc_tallies.get(op)));
 
         // Serialize counter stats early so we can resize columns early.
         let serialized_counters = KnownCounterKind::ALL.map(|counter_kind| {
@@ -318,6 +366,7 @@ impl TreePainter {
 
             for s in serialized_alloc_tallies
                 .iter()
+                .chain([&serialized_retained])
                 .flatten()
                 .flat_map(AllocTally::as_array)
                 .map(|values| &values[column as usize])
@@ -327,22 +376,90 @@ impl TreePainter {
         }
 
         // Write time stats with iter and sample counts.
-        TreeColumnData::from_fn(|column| -> String {
-            let stat: &dyn ToString = match column {
-                TreeColumn::Fastest => &stats.time.fastest,
-                TreeColumn::Slowest => &stats.time.slowest,
-                TreeColumn::Median => &stats.time.median,
-                TreeColumn::Mean => &stats.time.mean,
-                TreeColumn::Samples => &stats.sample_count,
-                TreeColumn::Iters => &stats.iter_count,
-            };
-            stat.to_string()
-        })
-        .as_ref::<str>()
-        .write(buf, &mut self.column_widths);
+        let time_columns = |stats: &Stats| {
+            TreeColumnData::from_fn(|column| -> String {
+                let stat: &dyn ToString = match column {
+                    TreeColumn::Fastest => &stats.time.fastest,
+                    TreeColumn::Slowest => &stats.time.slowest,
+                    TreeColumn::Median => &stats.time.median,
+                    TreeColumn::Mean => &stats.time.mean,
+                    TreeColumn::Samples => &stats.sample_count,
+                    TreeColumn::Iters => &stats.iter_count,
+                };
+                stat.to_string()
+            })
+        };
+
+        time_columns(stats).as_ref::<str>().write(buf, &mut self.column_widths);
 
         println!("{buf}");
 
+        // Write time stats of each thread role and thread, named under the
+        // leaf.
+        let roles = bench_stats
+            .roles
+            .iter()
+            .map(|role| (role.name().to_owned(), &role.stats.stats));
+        let threads =
+            bench_stats.threads.iter().enumerate().map(|(thread, stats)| {
+                (format!("thread {thread}"), &stats.stats)
+            });
+
+        for (name, sub_stats) in roles.chain(threads) {
+            buf.clear();
+            buf.push_str(&self.current_prefix);
+            buf.push_str(if is_last { "   " } else { "│  " });
+            buf.push_str(&name);
+
+            right_pad_buffer(buf, &mut self.max_name_span);
+
+            time_columns(sub_stats)
+                .as_ref::<str>()
+                .write(buf, &mut self.column_widths);
+
+            println!("{buf}");
+        }
+
+        // Write how evenly time was spread across threads. This is not written
+        // within columns because it compares threads rather than samples.
+        if let Some(spread) = bench_stats.thread_spread() {
+            prep_buffer(buf, &mut self.max_name_span);
+
+            TreeColumnData::from_first("spread:")
+                .write(buf, &mut self.column_widths);
+            println!("{buf}");
+
+            prep_buffer(buf, &mut self.max_name_span);
+            buf.push_str(&format!(
+                "  {}× (fastest: thread {}, slowest: thread {})",
+                util::fmt::format_f64(spread.ratio(), 3),
+                spread.fastest_thread(),
+                spread.slowest_thread(),
+            ));
+            println!("{buf}");
+        }
+
+        // Write the CPU each thread was pinned to.
+        if !pinned_cpus.is_empty() {
+            prep_buffer(buf, &mut self.max_name_span);
+
+            TreeColumnData::from_first("pinned:")
+                .write(buf, &mut self.column_widths);
+            println!("{buf}");
+
+            let mapping = pinned_cpus
+                .iter()
+                .enumerate()
+                .map(|(thread, cpu)| format!("thread {thread} → cpu {cpu}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            prep_buffer(buf, &mut self.max_name_span);
+            buf.push_str("  ");
+            buf.push_str(&mapping);
+            println!("{buf}");
+        }
+
         // Write counter stats.
         let counter_stats = serialized_counters.map(TreeColumnData);
         for counter_kind in KnownCounterKind::ALL {
@@ -388,10 +505,28 @@ impl TreePainter {
             }
         }
 
+        // Write bytes retained after each sample.
+        if let Some(retained) = &serialized_retained {
+            prep_buffer(buf, &mut self.max_name_span);
+
+            TreeColumnData::from_first("retained:")
+                .write(buf, &mut self.column_widths);
+            println!("{buf}");
+
+            for value in retained.as_array() {
+                prep_buffer(buf, &mut self.max_name_span);
+
+                TreeColumnData::from_fn(|column| {
+                    value[column as usize].as_str()
+                })
+                .write(buf, &mut self.column_widths);
+
+                println!("{buf}");
+            }
+        }
+
         // Write allocation tallies.
-        for op in
-            [AllocOp::Alloc, AllocOp::Dealloc, AllocOp::Grow, AllocOp::Shrink]
-        {
+        for op in AllocOp::DISPLAY {
             let Some(tallies) = &serialized_alloc_tallies[op as usize] else {
                 continue;
             };
@@ -413,6 +548,165 @@ impl TreePainter {
                 println!("{buf}");
             }
         }
+
+        // Write process memory usage. This is not written within columns
+        // because it is not associated with samples.
+        if stats.peak_rss.is_some() || stats.rss_delta.is_some() {
+            prep_buffer(buf, &mut self.max_name_span);
+
+            TreeColumnData::from_first("rss:")
+                .write(buf, &mut self.column_widths);
+            println!("{buf}");
+
+            prep_buffer(buf, &mut self.max_name_span);
+            buf.push_str("  ");
+
+            if let Some(peak) = stats.peak_rss {
+                buf.push_str("peak: ");
+                buf.push_str(&util::fmt::format_bytes(
+                    peak as f64,
+                    4,
+                    bytes_format,
+                ));
+            }
+
+            if let Some(delta) = stats.rss_delta {
+                if stats.peak_rss.is_some() {
+                    buf.push_str(", ");
+                }
+
+                buf.push_str("delta: ");
+                if delta >= 0 {
+                    buf.push('+');
+                }
+                buf.push_str(&util::fmt::format_bytes(
+                    delta as f64,
+                    4,
+                    bytes_format,
+                ));
+            }
+
+            println!("{buf}");
+        }
+
+        // Write allocation size distribution. This is not written within
+        // columns to keep it compact.
+        let mut alloc_sizes = stats.alloc_sizes.non_empty().peekable();
+        if alloc_sizes.peek().is_some() {
+            prep_buffer(buf, &mut self.max_name_span);
+
+            TreeColumnData::from_first("sizes:")
+                .write(buf, &mut self.column_widths);
+            println!("{buf}");
+
+            prep_buffer(buf, &mut self.max_name_span);
+            buf.push_str("  ");
+
+            for (i, (max_size, count)) in alloc_sizes.enumerate() {
+                if i != 0 {
+                    buf.push_str(", ");
+                }
+
+                buf.push_str(&format!(
+                    "≤{}: {}",
+                    util::fmt::format_bytes(max_size as f64, 4, bytes_format),
+                    util::fmt::format_f64(count, 4),
+                ));
+            }
+
+            println!("{buf}");
+        }
+
+        // Write allocation call sites, with callers on following lines.
+        if !stats.alloc_call_sites.is_empty() {
+            prep_buffer(buf, &mut self.max_name_span);
+
+            TreeColumnData::from_first("call sites:")
+                .write(buf, &mut self.column_widths);
+            println!("{buf}");
+
+            for (call_site, count) in &stats.alloc_call_sites {
+                let count = format!("{}: ", util::fmt::format_f64(*count, 4));
+
+                for (i, frame) in call_site.lines().enumerate() {
+                    prep_buffer(buf, &mut self.max_name_span);
+                    buf.push_str("  ");
+
+                    if i == 0 {
+                        buf.push_str(&count);
+                    } else {
+                        buf.extend(repeat(' ').take(count.len()));
+                    }
+
+                    buf.push_str(frame);
+                    println!("{buf}");
+                }
+            }
+        }
+    }
+
+    /// Writes the best complexity fit for the current parent node, formatting
+    /// the coefficient and intercept with `format_value`.
+    ///
+    /// These are not written within columns to avoid widening them.
+    fn complexity_row(
+        &mut self,
+        label: &str,
+        fit: ComplexityFit,
+        format_value: impl Fn(f64) -> String,
+    ) {
+        let buf = &mut self.write_buf;
+        buf.clear();
+
+        let big_o = fit.big_o();
+        buf.push_str(&self.current_prefix);
+        buf.push_str(&format!("~ {label}: {big_o}"));
+
+        right_pad_buffer(buf, &mut self.max_name_span);
+
+        buf.push_str(&format_value(fit.coefficient()));
+        if big_o != BigO::Constant {
+            buf.push_str(" × ");
+            buf.push_str(big_o.term());
+        }
+
+        let intercept = fit.intercept();
+        if intercept != 0.0 {
+            buf.push_str(if intercept < 0.0 { " - " } else { " + " });
+            buf.push_str(&format_value(intercept.abs()));
+        }
+
+        println!("{buf}");
+    }
+
+    /// Writes the scaling of a thread count relative to 1 thread for the
+    /// current parent node.
+    ///
+    /// Values are not written within columns to avoid widening them.
+    fn scaling_row(&mut self, scaling: &ThreadScaling) {
+        let buf = &mut self.write_buf;
+        buf.clear();
+
+        buf.push_str(&self.current_prefix);
+        buf.push_str(&format!("~ t={}", scaling.thread_count()));
+
+        right_pad_buffer(buf, &mut self.max_name_span);
+
+        match (scaling.speedup(), scaling.efficiency()) {
+            (Some(speedup), Some(efficiency)) => buf.push_str(&format!(
+                "{}× speedup, {}% efficiency",
+                util::fmt::format_f64(speedup, 3),
+                util::fmt::format_f64(efficiency * 100.0, 3),
+            )),
+            _ => buf.push_str("unknown speedup"),
+        }
+
+        for throughput in scaling.display_throughputs(self.bytes_format) {
+            buf.push_str(", ");
+            buf.push_str(&throughput);
+        }
+
+        println!("{buf}");
     }
 
     fn has_columns(&self) -> bool {
@@ -420,6 +714,61 @@ impl TreePainter {
     }
 }
 
+impl Reporter for TreePainter {
+    fn start_group(&mut self, group: &GroupInfo) {
+        self.start_parent(group.name(), group.is_last());
+    }
+
+    fn finish_group(&mut self, group: &GroupInfo) {
+        if let Some(fit) = group.time_complexity() {
+            self.complexity_row("time", fit, |nanos| {
+                let duration = FineDuration {
+                    picos: (nanos.max(0.0) * 1_000.0).round() as u128,
+                };
+                duration.to_string()
+            });
+        }
+
+        if let Some(fit) = group.alloc_complexity() {
+            let bytes_format = self.bytes_format;
+            self.complexity_row("alloc", fit, |bytes| {
+                util::fmt::format_bytes(bytes, 4, bytes_format)
+            });
+        }
+
+        for scaling in group.thread_scaling() {
+            self.scaling_row(scaling);
+        }
+
+        self.finish_parent();
+    }
+
+    fn start_bench(&mut self, bench: &BenchInfo) {
+        self.start_leaf(bench.name(), bench.is_last());
+    }
+
+    fn finish_bench(&mut self, bench: &BenchInfo, stats: Option<&BenchStats>) {
+        match stats {
+            Some(stats) => {
+                self.finish_leaf(bench.is_last(), stats, bench.pinned_cpus())
+            }
+            None => self.finish_empty_leaf(),
+        }
+    }
+
+    fn ignore_bench(&mut self, bench: &BenchInfo) {
+        self.ignore_leaf(bench.name(), bench.is_last());
+    }
+
+    fn bench_panicked(&mut self, _bench: &BenchInfo, message: &str) {
+        self.fail_leaf("FAILED", message);
+    }
+
+    fn bench_failed(&mut self, _bench: &BenchInfo, message: &str) {
+        self.fail_leaf("error", message);
+    }
+}
+
 /// Columns of the table next to the tree.
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub(crate) enum TreeColumn {
@@ -457,8 +806,15 @@ impl TreeColumn {
         self == last
     }
 
-    fn name(self) -> &'static str {
+    /// Returns the column heading.
+    ///
+    /// Allocation distributions are shown as minimums and maximums rather than
+    /// allocations of the fastest and slowest samples, which is consistent
+    /// with the times in those columns.
+    fn name(self, alloc_distribution: bool) -> &'static str {
         match self {
+            Self::Fastest if alloc_distribution => "min",
+            Self::Slowest if alloc_distribution => "max",
             Self::Fastest => "fastest",
             Self::Slowest => "slowest",
             Self::Median => "median",
@@ -0,0 +1,154 @@
+//! CPU affinity of threads.
+//!
+//! This is only supported on Linux, where it uses `sched_setaffinity`.
+
+/// Returns the CPUs the current thread is allowed to run on, in ascending
+/// order.
+///
+/// Returns `None` if affinity is not supported on this platform.
+pub(crate) fn current() -> Option<Vec<usize>> {
+    cfg_if::cfg_if! {
+        if #[cfg(target_os = "linux")] {
+            // SAFETY: `cpu_set_t` is plain data and is initialized by
+            // `sched_getaffinity` on success.
+            unsafe {
+                let mut set = std::mem::zeroed::<libc::cpu_set_t>();
+                let size = std::mem::size_of::<libc::cpu_set_t>();
+                if libc::sched_getaffinity(0, size, &mut set) != 0 {
+                    return None;
+                }
+
+                let max_cpus = 8 * size;
+                Some((0..max_cpus).filter(|&cpu| libc::CPU_ISSET(cpu, &set)).collect())
+            }
+        } else {
+            None
+        }
+    }
+}
+
+/// Restricts the current thread to run on `cpus`.
+///
+/// Returns `false` if this failed, such as if `cpus` are not available to the
+/// process or affinity is not supported on this platform.
+pub(crate) fn set_current(cpus: &[usize]) -> bool {
+    cfg_if::cfg_if! {
+        if #[cfg(target_os = "linux")] {
+            let size = std::mem::size_of::<libc::cpu_set_t>();
+
+            // `CPU_SET` panics for CPUs that do not fit in `cpu_set_t`.
+            let max_cpus = 8 * size;
+            if cpus.iter().any(|&cpu| cpu >= max_cpus) {
+                return false;
+            }
+
+            // SAFETY: `cpu_set_t` is plain data, and all CPUs are within its
+            // range.
+            unsafe {
+                let mut set = std::mem::zeroed::<libc::cpu_set_t>();
+                for &cpu in cpus {
+                    libc::CPU_SET(cpu, &mut set);
+                }
+
+                libc::sched_setaffinity(0, size, &set) == 0
+            }
+        } else {
+            _ = cpus;
+            false
+        }
+    }
+}
+
+/// Groups `cpus` by the physical core they belong to, ordered by their lowest
+/// CPU.
+///
+/// CPUs whose core cannot be determined are treated as separate cores.
+pub(crate) fn physical_cores(cpus: &[usize]) -> Vec<Vec<usize>> {
+    let mut cores = std::collections::BTreeMap::<usize, Vec<usize>>::new();
+
+    for &cpu in cpus {
+        let core = core_siblings(cpu)
+            .and_then(|siblings| siblings.into_iter().min())
+            .unwrap_or(cpu);
+
+        cores.entry(core).or_default().push(cpu);
+    }
+
+    cores.into_values().collect()
+}
+
+/// Returns the CPUs that share a physical core with `cpu`, including itself.
+fn core_siblings(cpu: usize) -> Option<Vec<usize>> {
+    cfg_if::cfg_if! {
+        if #[cfg(target_os = "linux")] {
+            let path = format!(
+                "/sys/devices/system/cpu/cpu{cpu}/topology/thread_siblings_list"
+            );
+            parse_cpu_list(std::fs::read_to_string(path).ok()?.trim())
+        } else {
+            _ = cpu;
+            None
+        }
+    }
+}
+
+/// Parses a list of CPUs in the Linux `cpulist` format, such as `0,2,4-7`.
+pub(crate) fn parse_cpu_list(list: &str) -> Option<Vec<usize>> {
+    let mut cpus = Vec::new();
+
+    for item in list.split(',') {
+        let item = item.trim();
+
+        match item.split_once('-') {
+            Some((start, end)) => {
+                let start: usize = start.trim().parse().ok()?;
+                let end: usize = end.trim().parse().ok()?;
+                if start > end {
+                    return None;
+                }
+                cpus.extend(start..=end);
+            }
+            None => cpus.push(item.parse().ok()?),
+        }
+    }
+
+    Some(cpus)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cpu_list() {
+        assert_eq!(parse_cpu_list("3"), Some(vec![3]));
+        assert_eq!(parse_cpu_list("0,2,4-7"), Some(vec![0, 2, 4, 5, 6, 7]));
+        assert_eq!(parse_cpu_list("1-1, 8"), Some(vec![1, 8]));
+
+        assert_eq!(parse_cpu_list(""), None);
+        assert_eq!(parse_cpu_list("0,"), None);
+        assert_eq!(parse_cpu_list("4-2"), None);
+        assert_eq!(parse_cpu_list("physical"), None);
+    }
+
+    #[test]
+    fn set_out_of_range() {
+        let original = current();
+
+        assert!(!set_current(&[usize::MAX]));
+        assert_eq!(current(), original);
+    }
+
+    #[test]
+    fn physical_cores_include_all_cpus() {
+        let Some(cpus) = current() else {
+            return;
+        };
+
+        let mut grouped: Vec<usize> =
+            physical_cores(&cpus).into_iter().flatten().collect();
+        grouped.sort_unstable();
+
+        assert_eq!(grouped, cpus);
+    }
+}
@@ -44,6 +44,10 @@ pub(crate) fn format_bytes(
     sig_figs: usize,
     bytes_format: BytesFormat,
 ) -> String {
+    if val < 0.0 {
+        return format!("-{}", format_bytes(-val, sig_figs, bytes_format));
+    }
+
     let (val, scale) = scale_value(val, bytes_format);
 
     let mut result = format_f64(val, sig_figs);
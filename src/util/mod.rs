@@ -1,4 +1,5 @@
 use std::{
+    any::Any,
     mem::ManuallyDrop,
     num::NonZeroUsize,
     sync::atomic::{AtomicUsize, Ordering::Relaxed},
@@ -9,7 +10,9 @@ use regex::Regex;
 #[macro_use]
 mod macros;
 
+pub mod affinity;
 pub mod fmt;
+pub mod rss;
 pub mod sort;
 pub mod split_vec;
 pub mod sync;
@@ -69,6 +72,17 @@ pub(crate) fn defer<F: FnOnce()>(f: F) -> impl Drop {
     Defer(ManuallyDrop::new(f))
 }
 
+/// Returns the message of a panic payload, if it is a string.
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "Box<dyn Any>"
+    }
+}
+
 /// Returns the index of `ptr` in the slice, assuming it is in the slice.
 #[inline]
 pub(crate) fn slice_ptr_index<T>(slice: &[T], ptr: *const T) -> usize {
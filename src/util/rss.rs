@@ -0,0 +1,118 @@
+//! Process resident set size (RSS) measurement.
+//!
+//! This accounts for memory not allocated through
+//! [`GlobalAlloc`](std::alloc::GlobalAlloc), such as `mmap` by C libraries.
+
+/// RSS at the start of a benchmark.
+pub(crate) struct RssTracker {
+    start: Option<u64>,
+}
+
+impl RssTracker {
+    /// Resets the process peak RSS, if possible, and records the current RSS.
+    pub fn start() -> Self {
+        reset_peak();
+        Self { start: current() }
+    }
+
+    /// Returns the peak RSS and the change in RSS since [`RssTracker::start`].
+    pub fn finish(self) -> (Option<u64>, Option<i64>) {
+        let delta = match (self.start, current()) {
+            (Some(start), Some(end)) => Some(end as i64 - start as i64),
+            _ => None,
+        };
+
+        (peak(), delta)
+    }
+}
+
+/// Returns the current RSS in bytes.
+fn current() -> Option<u64> {
+    cfg_if::cfg_if! {
+        if #[cfg(target_os = "linux")] {
+            proc_status_bytes("VmRSS:")
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns the peak RSS in bytes.
+///
+/// On Linux, this is since the last successful [`reset_peak`]. Elsewhere, it
+/// is the peak of the whole process.
+fn peak() -> Option<u64> {
+    cfg_if::cfg_if! {
+        if #[cfg(target_os = "linux")] {
+            proc_status_bytes("VmHWM:")
+        } else if #[cfg(unix)] {
+            // SAFETY: `getrusage` initializes `usage` on success.
+            let usage = unsafe {
+                let mut usage = std::mem::MaybeUninit::<libc::rusage>::uninit();
+                if libc::getrusage(libc::RUSAGE_SELF, usage.as_mut_ptr()) != 0 {
+                    return None;
+                }
+                usage.assume_init()
+            };
+
+            let max_rss = u64::try_from(usage.ru_maxrss).ok()?;
+
+            // macOS reports bytes rather than kilobytes.
+            if cfg!(target_vendor = "apple") {
+                Some(max_rss)
+            } else {
+                Some(max_rss * 1024)
+            }
+        } else {
+            None
+        }
+    }
+}
+
+/// Resets the peak RSS to the current RSS.
+///
+/// This requires Linux 4.0 and is a no-op elsewhere.
+fn reset_peak() {
+    #[cfg(target_os = "linux")]
+    {
+        _ = std::fs::write("/proc/self/clear_refs", "5");
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn proc_status_bytes(key: &str) -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    parse_status_kb(&status, key).map(|kb| kb * 1024)
+}
+
+/// Parses the kilobytes value of `key` in `/proc/self/status`.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_status_kb(status: &str, key: &str) -> Option<u64> {
+    let line = status.lines().find_map(|line| line.strip_prefix(key))?;
+    line.trim().strip_suffix("kB")?.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_status() {
+        let status = "Name:\tdivan\nVmHWM:\t   10240 kB\nVmRSS:\t    8192 kB\n";
+
+        assert_eq!(parse_status_kb(status, "VmHWM:"), Some(10240));
+        assert_eq!(parse_status_kb(status, "VmRSS:"), Some(8192));
+        assert_eq!(parse_status_kb(status, "VmSwap:"), None);
+        assert_eq!(parse_status_kb(status, "Name:"), None);
+    }
+
+    #[test]
+    #[cfg(all(target_os = "linux", not(miri)))]
+    fn current_and_peak() {
+        let current = current().unwrap();
+        let peak = peak().unwrap();
+
+        assert!(current > 0);
+        assert!(peak >= current);
+    }
+}
@@ -4,7 +4,8 @@
 
 use std::{
     ops::{Deref, DerefMut},
-    sync::atomic::*,
+    panic,
+    sync::{atomic::*, Condvar, Mutex, PoisonError},
 };
 
 /// Makes the wrapped value [`Send`] + [`Sync`] even though it isn't.
@@ -57,6 +58,87 @@ impl AtomicFlag {
     }
 }
 
+/// A [`std::sync::Barrier`] that can be poisoned to release waiting threads.
+///
+/// If a benchmarking thread panics, it poisons the barrier so that the other
+/// threads unwind rather than wait forever for it.
+pub(crate) struct Barrier {
+    state: Mutex<BarrierState>,
+    cvar: Condvar,
+    thread_count: usize,
+}
+
+struct BarrierState {
+    count: usize,
+    generation: usize,
+    is_poisoned: bool,
+}
+
+/// Unwind payload for threads released by a poisoned [`Barrier`].
+///
+/// This is not a string so that the original panic message takes precedence.
+pub(crate) struct BarrierPoisoned;
+
+impl Barrier {
+    pub fn new(thread_count: usize) -> Self {
+        Self {
+            state: Mutex::new(BarrierState {
+                count: 0,
+                generation: 0,
+                is_poisoned: false,
+            }),
+            cvar: Condvar::new(),
+            thread_count,
+        }
+    }
+
+    /// Blocks until all threads have called `wait`, and then returns `true`
+    /// for a single leader thread.
+    ///
+    /// Unwinds with [`BarrierPoisoned`] if the barrier is poisoned before all
+    /// threads arrive.
+    pub fn wait(&self) -> bool {
+        let mut state =
+            self.state.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if state.is_poisoned {
+            drop(state);
+            panic::resume_unwind(Box::new(BarrierPoisoned));
+        }
+
+        let generation = state.generation;
+        state.count += 1;
+
+        if state.count < self.thread_count {
+            state = self
+                .cvar
+                .wait_while(state, |state| {
+                    state.generation == generation && !state.is_poisoned
+                })
+                .unwrap_or_else(PoisonError::into_inner);
+
+            if state.generation == generation {
+                drop(state);
+                panic::resume_unwind(Box::new(BarrierPoisoned));
+            }
+
+            false
+        } else {
+            state.count = 0;
+            state.generation = state.generation.wrapping_add(1);
+            self.cvar.notify_all();
+            true
+        }
+    }
+
+    /// Releases all current and future waiters by making them unwind.
+    pub fn poison(&self) {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner).is_poisoned =
+            true;
+        self.cvar.notify_all();
+    }
+}
+
 /// Prevents false sharing by aligning to the cache line.
 #[derive(Clone, Copy)]
 #[repr(align(64))]
@@ -1,4 +1,5 @@
 use std::{
+    any::Any,
     num::NonZeroUsize,
     panic::AssertUnwindSafe,
     ptr::NonNull,
@@ -9,7 +10,10 @@ use std::{
     thread::Thread,
 };
 
-use crate::util::{defer, sync::SyncWrap};
+use crate::{
+    benchmark::BenchFailure,
+    util::{defer, sync::SyncWrap},
+};
 
 /// Reusable threads for broadcasting tasks.
 ///
@@ -34,6 +38,9 @@ use crate::util::{defer, sync::SyncWrap};
 ///
 /// 4. The main thread executes the `Task` like auxiliary threads. It then waits
 ///    until the reference count is 0 before returning.
+///
+/// Panics on any thread are caught, and the most informative panic payload is
+/// returned to the caller of `broadcast`.
 pub(crate) struct ThreadPool {
     threads: Mutex<Vec<mpsc::SyncSender<Task>>>,
 }
@@ -44,13 +51,16 @@ impl ThreadPool {
     }
 
     /// Performs the given task and pushes the results into a `vec`.
+    ///
+    /// Results of threads that panicked are `None`.
     #[inline]
     pub fn par_extend<T, F>(
         &self,
         vec: &mut Vec<Option<T>>,
         aux_threads: usize,
         task: F,
-    ) where
+    ) -> std::thread::Result<()>
+    where
         F: Sync + Fn(usize) -> T,
         T: Sync + Send,
     {
@@ -68,16 +78,22 @@ impl ThreadPool {
 
             self.broadcast(aux_threads, move |index| {
                 ptr.add(index).write(Some(task(index)));
-            });
+            })
         }
     }
 
     /// Performs the given task across the current thread and auxiliary worker
     /// threads.
     ///
-    /// This function returns once all threads complete the task.
+    /// This function returns once all threads complete the task. If any thread
+    /// panicked, its panic payload is returned, preferring string messages
+    /// over other payloads.
     #[inline]
-    pub fn broadcast<F>(&self, aux_threads: usize, task: F)
+    pub fn broadcast<F>(
+        &self,
+        aux_threads: usize,
+        task: F,
+    ) -> std::thread::Result<()>
     where
         F: Sync + Fn(usize),
     {
@@ -88,12 +104,16 @@ impl ThreadPool {
             let task = TaskShared::new(aux_threads, task);
             let task = Task { shared: NonNull::from(&task).cast() };
 
-            self.broadcast_task(aux_threads, task);
+            self.broadcast_task(aux_threads, task)
         }
     }
 
     /// Type-erased monomorphized implementation for `broadcast`.
-    unsafe fn broadcast_task(&self, aux_threads: usize, task: Task) {
+    unsafe fn broadcast_task(
+        &self,
+        aux_threads: usize,
+        task: Task,
+    ) -> std::thread::Result<()> {
         // Send task to auxiliary threads.
         if aux_threads > 0 {
             let threads = &mut *self
@@ -126,9 +146,19 @@ impl ThreadPool {
             std::thread::park();
         }
 
-        // Don't drop our result until other threads finish, in case the panic
-        // error's drop handler itself also panics.
-        drop(main_result);
+        if let Err(payload) = main_result {
+            task.record_panic(payload);
+        }
+
+        let panic = task
+            .shared
+            .as_ref()
+            .panic
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+
+        panic.map_or(Ok(()), Err)
     }
 
     #[cfg(test)]
@@ -165,6 +195,43 @@ impl Task {
 
         (shared.task_fn_ptr)(shared_ptr.cast(), thread_id);
     }
+
+    /// Stores the panic payload of a thread that ran this task.
+    ///
+    /// The first payload with a message is kept, since other payloads are
+    /// often secondary panics caused by the first, such as from a poisoned
+    /// barrier. Messages are strings or errors returned by the benchmark.
+    ///
+    /// # Safety
+    ///
+    /// This task must not have outlived the `TaskShared` it came from.
+    unsafe fn record_panic(&self, payload: Box<dyn Any + Send>) {
+        let is_str = |payload: &Box<dyn Any + Send>| {
+            payload.is::<&str>()
+                || payload.is::<String>()
+                || payload.is::<BenchFailure>()
+        };
+
+        let mut panic = self
+            .shared
+            .as_ref()
+            .panic
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        let should_replace = match &*panic {
+            None => true,
+            Some(current) => !is_str(current) && is_str(&payload),
+        };
+
+        // Drop the discarded payload outside of the lock, in case its drop
+        // handler panics.
+        let discarded =
+            if should_replace { panic.replace(payload) } else { Some(payload) };
+
+        drop(panic);
+        drop(discarded);
+    }
 }
 
 /// Data stored on the main thread that gets shared with auxiliary threads.
@@ -187,6 +254,9 @@ struct TaskShared<F> {
     /// Performs `*result = Some(task_fn(thread))`.
     task_fn_ptr: unsafe fn(task: *const TaskShared<()>, thread: usize),
 
+    /// The panic payload returned by `broadcast`.
+    panic: Mutex<Option<Box<dyn Any + Send>>>,
+
     /// Stores the closure state of the provided task.
     ///
     /// This must be stored as the last field so that all other fields are in
@@ -213,6 +283,7 @@ impl<F> TaskShared<F> {
             main_thread: std::thread::current(),
             ref_count: AtomicUsize::new(aux_threads),
             task_fn_ptr: call::<F>,
+            panic: Mutex::new(None),
             task_fn,
         }
     }
@@ -249,6 +320,13 @@ fn spawn(additional: NonZeroUsize, threads: &mut Vec<mpsc::SyncSender<Task>>) {
                             task.run(thread_id)
                         }));
 
+                    // Hand the panic to the main thread before notifying it.
+                    //
+                    // SAFETY: The task is valid until `ref_count == 0`.
+                    if let Err(payload) = result {
+                        unsafe { task.record_panic(payload) };
+                    }
+
                     // Decrement the `ref_count` count to notify the main thread
                     // that we finished our work.
                     //
@@ -270,10 +348,6 @@ fn spawn(additional: NonZeroUsize, threads: &mut Vec<mpsc::SyncSender<Task>>) {
                             main_thread.unpark();
                         }
                     }
-
-                    // Don't drop our result until after notifying the main thread,
-                    // in case the panic error's drop handler itself also panics.
-                    drop(result);
                 }
 
                 std::mem::forget(panic_guard);
@@ -305,7 +379,9 @@ mod tests {
             let mut results = Vec::new();
             let expected = (0..total_threads).map(Some).collect::<Vec<_>>();
 
-            test_pool.par_extend(&mut results, aux_threads, |index| index);
+            test_pool
+                .par_extend(&mut results, aux_threads, |index| index)
+                .unwrap();
 
             assert_eq!(results, expected);
             assert_eq!(test_pool.aux_thread_count(), final_aux_threads);
@@ -332,11 +408,13 @@ mod tests {
 
         let test_pool = ThreadPool::new();
 
-        test_pool.broadcast(10, |thread_id| {
-            if thread_id > 0 {
-                std::thread::sleep(Duration::from_millis(10));
-            }
-        });
+        test_pool
+            .broadcast(10, |thread_id| {
+                if thread_id > 0 {
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+            })
+            .unwrap();
     }
 
     /// Checks that thread ID 0 refers to the main thread.
@@ -346,10 +424,34 @@ mod tests {
 
         let main_thread = std::thread::current().id();
 
-        test_pool.broadcast(10, |thread_id| {
-            let is_main = main_thread == std::thread::current().id();
-            assert_eq!(is_main, thread_id == 0);
-        });
+        test_pool
+            .broadcast(10, |thread_id| {
+                let is_main = main_thread == std::thread::current().id();
+                assert_eq!(is_main, thread_id == 0);
+            })
+            .unwrap();
+    }
+
+    /// Checks that a panic on an auxiliary thread is returned in preference
+    /// to non-string panics on other threads.
+    #[test]
+    fn broadcast_panic() {
+        let test_pool = ThreadPool::new();
+
+        let mut results = Vec::new();
+        let payload = test_pool
+            .par_extend(&mut results, 4, |thread_id| match thread_id {
+                2 => panic!("thread {thread_id} failed"),
+                3 => std::panic::resume_unwind(Box::new(())),
+                _ => thread_id,
+            })
+            .unwrap_err();
+
+        assert_eq!(crate::util::panic_message(&*payload), "thread 2 failed");
+        assert_eq!(results, [Some(0), Some(1), None, None, Some(4)]);
+
+        // The pool is still usable after a panic.
+        test_pool.broadcast(4, crate::black_box_drop).unwrap();
     }
 }
 
@@ -381,7 +483,7 @@ mod benches {
             move || pool.broadcast(aux_threads, crate::black_box_drop);
 
         // Warmup to spawn threads.
-        benched();
+        benched().unwrap();
 
         bencher.bench(benched);
     }
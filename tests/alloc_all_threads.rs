@@ -0,0 +1,34 @@
+// Tests that `AllocProfiler::with_all_threads` counts allocations in threads
+// not spawned by Divan.
+
+// Miri cannot discover benchmarks.
+#![cfg(not(miri))]
+
+use divan::AllocProfiler;
+
+use common::divan;
+
+mod common;
+
+#[global_allocator]
+static ALLOC: AllocProfiler = AllocProfiler::system().with_all_threads();
+
+const SIZE: usize = 1 << 20;
+
+#[divan::bench(sample_count = 3, sample_size = 2)]
+fn other_thread() {
+    std::thread::scope(|scope| {
+        scope.spawn(|| divan::black_box(Vec::<u8>::with_capacity(SIZE)));
+    });
+}
+
+#[test]
+fn all_threads() {
+    let report = divan().run_benches();
+
+    let stats = report.benches().next().unwrap().stats().unwrap();
+
+    // Spawning only makes small allocations on the benchmarking thread.
+    assert!(stats.alloc().size.fastest >= SIZE as f64);
+    assert!(stats.dealloc().size.fastest >= SIZE as f64);
+}
@@ -0,0 +1,50 @@
+// Tests that `AllocProfiler::with_call_sites` attributes allocations.
+
+// Miri cannot discover benchmarks.
+#![cfg(not(miri))]
+
+use divan::AllocProfiler;
+
+use common::divan;
+
+mod common;
+
+#[global_allocator]
+static ALLOC: AllocProfiler = AllocProfiler::system().with_call_sites(1);
+
+#[inline(never)]
+fn make_boxes() -> [Box<u64>; 2] {
+    [Box::new(divan::black_box(1)), Box::new(divan::black_box(2))]
+}
+
+#[inline(never)]
+fn make_vec() -> Vec<u8> {
+    Vec::with_capacity(divan::black_box(8))
+}
+
+#[divan::bench(sample_count = 3, sample_size = 2)]
+fn allocs() -> ([Box<u64>; 2], Vec<u8>) {
+    (make_boxes(), make_vec())
+}
+
+#[test]
+fn call_sites() {
+    let report = divan().run_benches();
+
+    let bench = report.benches().next().unwrap();
+    let call_sites: Vec<(&str, f64)> =
+        bench.stats().unwrap().alloc_call_sites().collect();
+
+    // Each `Box::new` is a separate call site.
+    let count_of = |name: &str| -> f64 {
+        call_sites
+            .iter()
+            .filter(|(call_site, _)| call_site.starts_with(name))
+            .map(|(_, count)| count)
+            .sum()
+    };
+
+    assert_eq!(count_of("alloc_call_sites::make_boxes at "), 2.0);
+    assert_eq!(count_of("alloc_call_sites::make_vec at "), 1.0);
+    assert_eq!(call_sites.len(), 3, "{call_sites:#?}");
+}
@@ -0,0 +1,59 @@
+// Tests that `AllocProfiler::with_alloc_categories` counts zeroed,
+// over-aligned, and huge allocations.
+
+// Miri cannot discover benchmarks.
+#![cfg(not(miri))]
+
+use divan::AllocProfiler;
+
+use common::divan;
+
+mod common;
+
+const HUGE: usize = 4096;
+
+#[global_allocator]
+static ALLOC: AllocProfiler =
+    AllocProfiler::system().with_alloc_categories(HUGE);
+
+#[repr(align(64))]
+struct Aligned(#[allow(dead_code)] [u8; 64]);
+
+#[divan::bench(sample_count = 3, sample_size = 2)]
+fn allocs() -> (Vec<u8>, Box<Aligned>, Vec<u8>) {
+    (vec![0; 8], Box::new(Aligned([1; 64])), Vec::with_capacity(HUGE))
+}
+
+#[divan::bench(sample_count = 3, sample_size = 2)]
+fn grow() -> Vec<u8> {
+    let mut vec = Vec::<u8>::with_capacity(8);
+    vec.reserve_exact(HUGE);
+    vec
+}
+
+#[test]
+fn alloc_categories() {
+    let report = divan().run_benches();
+
+    let bench = report.benches().find(|b| b.name() == "allocs").unwrap();
+    let stats = bench.stats().unwrap();
+
+    assert_eq!(stats.alloc().count.fastest, 3.0);
+
+    assert_eq!(stats.zeroed().count.fastest, 1.0);
+    assert_eq!(stats.zeroed().size.fastest, 8.0);
+
+    assert_eq!(stats.aligned().count.fastest, 1.0);
+    assert_eq!(stats.aligned().size.fastest, 64.0);
+
+    assert_eq!(stats.huge().count.fastest, 1.0);
+    assert_eq!(stats.huge().size.fastest, HUGE as f64);
+
+    // Growing past the threshold is huge.
+    let bench = report.benches().find(|b| b.name() == "grow").unwrap();
+    let stats = bench.stats().unwrap();
+
+    assert_eq!(stats.grow().count.fastest, 1.0);
+    assert_eq!(stats.huge().count.fastest, 1.0);
+    assert_eq!(stats.huge().size.fastest, HUGE as f64);
+}
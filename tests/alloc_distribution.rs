@@ -0,0 +1,45 @@
+// Tests that allocation distributions are computed over all samples rather
+// than from the samples with the fastest, slowest, and median times.
+
+// Miri cannot discover benchmarks.
+#![cfg(not(miri))]
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use divan::{report::Distribution, AllocProfiler};
+
+use common::divan;
+
+mod common;
+
+#[global_allocator]
+static ALLOC: AllocProfiler = AllocProfiler::system();
+
+/// Allocates on every other sample.
+#[divan::bench(sample_count = 4, sample_size = 1)]
+fn bimodal() -> Option<Vec<u8>> {
+    static SHOULD_ALLOC: AtomicBool = AtomicBool::new(false);
+
+    let should_alloc = !SHOULD_ALLOC.fetch_xor(true, Ordering::Relaxed);
+    should_alloc.then(|| Vec::with_capacity(8))
+}
+
+#[test]
+fn alloc_distribution() {
+    let report = divan().run_benches();
+
+    let stats = report.benches().next().unwrap().stats().unwrap();
+    let alloc = stats.alloc_distribution();
+
+    assert_eq!(
+        alloc.count,
+        Distribution { min: 0.0, max: 1.0, median: 0.5, mean: 0.5 }
+    );
+    assert_eq!(
+        alloc.size,
+        Distribution { min: 0.0, max: 8.0, median: 4.0, mean: 4.0 }
+    );
+
+    assert_eq!(alloc.count.mean, stats.alloc().count.mean);
+    assert_eq!(stats.max_alloc_distribution().size.max, 8.0);
+}
@@ -0,0 +1,69 @@
+// Tests that `alloc_profile = false` makes `AllocProfiler` skip tallying.
+
+// Miri cannot discover benchmarks.
+#![cfg(not(miri))]
+
+use divan::{AllocProfiler, Divan};
+
+use common::quick_divan;
+
+mod common;
+
+#[global_allocator]
+static ALLOC: AllocProfiler = AllocProfiler::system();
+
+#[divan::bench]
+fn profiled() -> Vec<u8> {
+    Vec::with_capacity(64)
+}
+
+#[divan::bench(alloc_profile = false)]
+fn unprofiled() -> Vec<u8> {
+    Vec::with_capacity(64)
+}
+
+#[divan::bench(alloc_profile = false, max_allocs = 0)]
+fn unprofiled_limit() -> Vec<u8> {
+    Vec::with_capacity(64)
+}
+
+fn alloc_counts(divan: Divan) -> Vec<(String, f64)> {
+    divan
+        .run_benches()
+        .benches()
+        .map(|bench| {
+            let count = bench.stats().unwrap().alloc().count.mean;
+            (bench.name().to_owned(), count)
+        })
+        .collect()
+}
+
+#[test]
+fn option() {
+    assert_eq!(
+        alloc_counts(quick_divan(3)),
+        [
+            ("profiled".to_owned(), 1.0),
+            ("unprofiled".to_owned(), 0.0),
+            ("unprofiled_limit".to_owned(), 0.0),
+        ]
+    );
+
+    // Limits are not checked when profiling is disabled.
+    quick_divan(3).test_benches();
+}
+
+#[test]
+fn override_option() {
+    let divan = || quick_divan(3).skip_exact("alloc_profile::unprofiled_limit");
+
+    assert_eq!(
+        alloc_counts(divan().alloc_profile(false)),
+        [("profiled".to_owned(), 0.0), ("unprofiled".to_owned(), 0.0)]
+    );
+
+    assert_eq!(
+        alloc_counts(divan().alloc_profile(true)),
+        [("profiled".to_owned(), 1.0), ("unprofiled".to_owned(), 1.0)]
+    );
+}
@@ -0,0 +1,31 @@
+// Tests that `AllocProfiler::with_size_histogram` counts allocations by size.
+
+// Miri cannot discover benchmarks.
+#![cfg(not(miri))]
+
+use divan::AllocProfiler;
+
+use common::divan;
+
+mod common;
+
+#[global_allocator]
+static ALLOC: AllocProfiler = AllocProfiler::system().with_size_histogram();
+
+#[divan::bench(sample_count = 3, sample_size = 2)]
+fn allocs() -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+    let mut grown = Vec::<u8>::with_capacity(1);
+    grown.reserve_exact(100);
+
+    (Vec::with_capacity(3), Vec::with_capacity(4), grown)
+}
+
+#[test]
+fn alloc_sizes() {
+    let report = divan().run_benches();
+
+    let bench = report.benches().next().unwrap();
+    let sizes: Vec<(u64, f64)> = bench.stats().unwrap().alloc_sizes().collect();
+
+    assert_eq!(sizes, [(1, 1.0), (4, 2.0), (128, 1.0)]);
+}
@@ -0,0 +1,111 @@
+// Tests that benchmarks returning `Err` are reported as failures without
+// stopping the rest of the run.
+
+// Miri cannot discover benchmarks.
+#![cfg(not(miri))]
+
+use std::{
+    fmt,
+    panic::{self, AssertUnwindSafe},
+};
+
+use divan::Bencher;
+
+use common::quick_divan;
+
+mod common;
+
+#[derive(Debug)]
+struct FixtureError(&'static str);
+
+impl fmt::Display for FixtureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "missing fixture '{}'", self.0)
+    }
+}
+
+#[divan::bench]
+fn a_ok() -> Result<u64, FixtureError> {
+    Ok(divan::black_box(1))
+}
+
+#[divan::bench]
+fn b_err() -> Result<u64, FixtureError> {
+    Err(FixtureError("b.json"))
+}
+
+#[divan::bench]
+fn c_setup_err(bencher: Bencher) -> Result<(), FixtureError> {
+    let _fixture: &str = Err(FixtureError("c.json"))?;
+
+    bencher.bench(|| divan::black_box(1));
+    Ok(())
+}
+
+#[divan::bench(args = [1, 2])]
+fn d_args(n: u64) -> Result<u64, String> {
+    if n == 2 {
+        Err(format!("bad arg {n}"))
+    } else {
+        Ok(divan::black_box(n))
+    }
+}
+
+// Errors without `Display` are treated as regular outputs.
+#[divan::bench]
+fn e_not_display() -> Result<u64, ()> {
+    Err(())
+}
+
+// Errors checked within the benchmarked closure fail on any thread.
+#[divan::bench(threads = 2)]
+fn f_closure_err(bencher: Bencher) {
+    bencher.bench(|| divan::check(divan::black_box("f").parse::<u64>()));
+}
+
+#[test]
+fn errors() {
+    let report = quick_divan(3).run_benches();
+
+    let results: Vec<(&str, Option<&str>, bool)> = report
+        .benches()
+        .map(|bench| (bench.path(), bench.error(), bench.stats().is_some()))
+        .collect();
+
+    assert_eq!(
+        results,
+        [
+            ("bench_errors::a_ok", None, true),
+            ("bench_errors::b_err", Some("missing fixture 'b.json'"), false),
+            (
+                "bench_errors::c_setup_err",
+                Some("missing fixture 'c.json'"),
+                false
+            ),
+            ("bench_errors::d_args::1", None, true),
+            ("bench_errors::d_args::2", Some("bad arg 2"), false),
+            ("bench_errors::e_not_display", None, true),
+            (
+                "bench_errors::f_closure_err",
+                Some("invalid digit found in string"),
+                false
+            ),
+        ]
+    );
+}
+
+// Failures are carried by each run's unwinding, so failing runs may be
+// concurrent with other tests.
+#[test]
+fn test_benches() {
+    // Testing runs every benchmark before failing.
+    let result =
+        panic::catch_unwind(AssertUnwindSafe(|| quick_divan(3).test_benches()));
+    let payload = result.unwrap_err();
+    assert_eq!(
+        payload.downcast_ref::<String>().map(String::as_str),
+        Some("4 benchmark(s) failed")
+    );
+
+    quick_divan(3).skip_regex("err|d_args").test_benches();
+}
@@ -0,0 +1,52 @@
+// Tests that `Divan::check_leaks` fails benchmarks run as tests that do not
+// free all allocated memory.
+
+// Miri cannot discover benchmarks.
+#![cfg(not(miri))]
+
+use std::sync::OnceLock;
+
+use divan::AllocProfiler;
+
+use common::divan;
+
+mod common;
+
+#[global_allocator]
+static ALLOC: AllocProfiler = AllocProfiler::system();
+
+#[divan::bench]
+fn no_leak() -> Vec<u8> {
+    Vec::with_capacity(64)
+}
+
+#[divan::bench]
+fn lazy_init() -> usize {
+    static CACHE: OnceLock<Vec<u8>> = OnceLock::new();
+    CACHE.get_or_init(|| vec![0; 64]).len()
+}
+
+#[divan::bench]
+fn leak() -> &'static mut [u8; 16] {
+    Box::leak(Box::new([1; 16]))
+}
+
+#[test]
+fn no_leaks() {
+    divan().skip_exact("check_leaks::leak").check_leaks(true).test_benches();
+}
+
+#[test]
+fn disabled() {
+    divan().test_benches();
+}
+
+#[test]
+#[should_panic(expected = "benchmark leaked 16 bytes in 1 allocation")]
+fn leaks() {
+    divan()
+        .skip_exact("check_leaks::no_leak")
+        .skip_exact("check_leaks::lazy_init")
+        .check_leaks(true)
+        .test_benches();
+}
@@ -0,0 +1,18 @@
+// Helpers shared by integration tests.
+
+// Not every test uses every helper.
+#![allow(dead_code)]
+
+use divan::Divan;
+
+/// Returns `Divan` for running only the benchmarks of the current test.
+pub fn divan() -> Divan {
+    // Skip internal benchmarks enabled by workspace feature unification.
+    Divan::default().skip_regex("^divan::")
+}
+
+/// Returns `Divan` for quickly running the benchmarks of the current test with
+/// `sample_count` samples of 2 iterations, overriding benchmark options.
+pub fn quick_divan(sample_count: u32) -> Divan {
+    divan().sample_count(sample_count).sample_size(2)
+}
@@ -0,0 +1,83 @@
+// Tests that `keep_going` reports panicking benchmarks as failures without
+// stopping the rest of the run.
+
+// Miri cannot discover benchmarks.
+#![cfg(not(miri))]
+
+use std::panic::{self, AssertUnwindSafe};
+
+use divan::Bencher;
+
+use common::quick_divan;
+
+mod common;
+
+#[divan::bench]
+fn a_ok() -> u64 {
+    divan::black_box(1)
+}
+
+#[divan::bench]
+fn b_panic() {
+    panic!("main thread panic");
+}
+
+// Only one auxiliary thread panics, so the others must not wait for it.
+#[divan::bench(threads = [4, 1])]
+fn c_aux_panic(bencher: Bencher) {
+    bencher.bench(|| {
+        if std::thread::current().name() == Some("divan-2") {
+            panic!("aux thread panic");
+        }
+    });
+}
+
+#[divan::bench]
+fn d_ok() -> u64 {
+    divan::black_box(2)
+}
+
+#[test]
+fn keep_going() {
+    let report = quick_divan(3).keep_going(true).run_benches();
+
+    let results: Vec<(&str, Option<&str>, bool)> = report
+        .benches()
+        .map(|bench| {
+            (bench.path(), bench.panic_message(), bench.stats().is_some())
+        })
+        .collect();
+
+    // Remaining thread counts are skipped after a panic.
+    assert_eq!(
+        results,
+        [
+            ("keep_going::a_ok", None, true),
+            ("keep_going::b_panic", Some("main thread panic"), false),
+            ("keep_going::c_aux_panic::t=1", None, true),
+            ("keep_going::c_aux_panic::t=4", Some("aux thread panic"), false),
+            ("keep_going::d_ok", None, true),
+        ]
+    );
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        quick_divan(3).keep_going(true).test_benches()
+    }));
+    let payload = result.unwrap_err();
+    assert_eq!(
+        payload.downcast_ref::<String>().map(String::as_str),
+        Some("2 benchmark(s) failed")
+    );
+}
+
+#[test]
+fn stop_on_panic() {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        quick_divan(3).skip_exact("keep_going::b_panic").run_benches()
+    }));
+    let payload = result.unwrap_err();
+    assert_eq!(
+        payload.downcast_ref::<&str>().copied(),
+        Some("aux thread panic")
+    );
+}
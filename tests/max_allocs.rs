@@ -0,0 +1,71 @@
+// Tests that `max_allocs` fails benchmarks that allocate too much.
+
+// Miri cannot discover benchmarks.
+#![cfg(not(miri))]
+
+use divan::AllocProfiler;
+
+use common::quick_divan;
+
+mod common;
+
+#[global_allocator]
+static ALLOC: AllocProfiler = AllocProfiler::system();
+
+#[divan::bench(max_allocs = 0)]
+fn no_alloc() -> u64 {
+    (0..100).map(divan::black_box).sum()
+}
+
+#[divan::bench(max_allocs = 1)]
+fn two_allocs() -> (Vec<u8>, Vec<u8>) {
+    (Vec::with_capacity(8), Vec::with_capacity(8))
+}
+
+#[divan::bench]
+fn assert_allocs(bencher: divan::Bencher) {
+    bencher.assert_allocs(0).bench(|| Box::new(divan::black_box(1)));
+}
+
+#[test]
+fn within_limit() {
+    quick_divan(3)
+        .skip_exact("max_allocs::two_allocs")
+        .skip_exact("max_allocs::assert_allocs")
+        .test_benches();
+    quick_divan(3)
+        .skip_exact("max_allocs::two_allocs")
+        .skip_exact("max_allocs::assert_allocs")
+        .run_benches();
+}
+
+#[test]
+#[should_panic(
+    expected = "benchmark exceeded `max_allocs = 1` per iteration with 2 allocations over 1 iteration:\n  alloc:   2 (16 bytes)"
+)]
+fn test_exceeds_limit() {
+    quick_divan(3)
+        .skip_exact("max_allocs::no_alloc")
+        .skip_exact("max_allocs::assert_allocs")
+        .test_benches();
+}
+
+#[test]
+#[should_panic(
+    expected = "benchmark exceeded `max_allocs = 1` per iteration with 4 allocations over 2 iterations:\n  alloc:   4 (32 bytes)"
+)]
+fn bench_exceeds_limit() {
+    quick_divan(3)
+        .skip_exact("max_allocs::no_alloc")
+        .skip_exact("max_allocs::assert_allocs")
+        .run_benches();
+}
+
+#[test]
+#[should_panic(expected = "benchmark exceeded `max_allocs = 0`")]
+fn bencher_exceeds_limit() {
+    quick_divan(3)
+        .skip_exact("max_allocs::no_alloc")
+        .skip_exact("max_allocs::two_allocs")
+        .test_benches();
+}
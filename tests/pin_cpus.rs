@@ -0,0 +1,120 @@
+// Tests pinning benchmarking threads to CPUs.
+
+// Miri cannot discover benchmarks.
+#![cfg(all(not(miri), target_os = "linux"))]
+
+use std::{
+    sync::atomic::{AtomicUsize, Ordering::Relaxed},
+    time::Duration,
+};
+
+use divan::{report::Report, Bencher, CpuPin};
+
+use common::quick_divan;
+
+mod common;
+
+/// The CPUs the main thread ran on while benchmarking `main_cpu`, as bits.
+static MAIN_CPUS: AtomicUsize = AtomicUsize::new(0);
+
+/// The CPUs the main thread ran on while benchmarking `custom_cpu`, as bits.
+static CUSTOM_CPUS: AtomicUsize = AtomicUsize::new(0);
+
+fn record_cpu(cpus: &AtomicUsize) {
+    // SAFETY: `sched_getcpu` has no preconditions.
+    let cpu = unsafe { libc::sched_getcpu() };
+    if (0..usize::BITS as i32).contains(&cpu) {
+        cpus.fetch_or(1 << cpu, Relaxed);
+    }
+}
+
+#[divan::bench(pin = [0])]
+fn main_cpu() {
+    record_cpu(&MAIN_CPUS);
+}
+
+#[divan::bench(pin = [0])]
+fn custom_cpu(bencher: Bencher) {
+    bencher.bench_custom(|_| {
+        record_cpu(&CUSTOM_CPUS);
+        Duration::from_nanos(1)
+    });
+}
+
+#[divan::bench(threads = [1, 2], pin = "physical-cores")]
+fn physical_cores() {}
+
+#[divan::bench(threads = 2, pin = "physical-cores")]
+fn local(bencher: Bencher) {
+    bencher.bench_local(|| {});
+}
+
+#[divan::bench(threads = 2)]
+fn unpinned() {}
+
+/// Returns the CPUs the current thread is allowed to run on.
+fn allowed_cpus() -> Vec<usize> {
+    // SAFETY: `cpu_set_t` is plain data and is initialized by
+    // `sched_getaffinity` on success.
+    unsafe {
+        let mut set = std::mem::zeroed::<libc::cpu_set_t>();
+        let size = std::mem::size_of::<libc::cpu_set_t>();
+        assert_eq!(libc::sched_getaffinity(0, size, &mut set), 0);
+
+        (0..8 * size).filter(|&cpu| libc::CPU_ISSET(cpu, &set)).collect()
+    }
+}
+
+fn pinned_cpus<'a>(report: &'a Report, name: &str) -> Vec<&'a [usize]> {
+    report
+        .benches()
+        .filter(|bench| bench.path().contains(name))
+        .map(|bench| bench.pinned_cpus())
+        .collect()
+}
+
+#[test]
+fn pin_cpus() {
+    let allowed = allowed_cpus();
+    let report = quick_divan(4).run_benches();
+
+    // Affinity is restored after benchmarking.
+    assert_eq!(allowed_cpus(), allowed);
+
+    // CPU 0 is not available to every process.
+    for (name, cpus) in [("main_cpu", &MAIN_CPUS), ("custom_cpu", &CUSTOM_CPUS)]
+    {
+        if allowed.contains(&0) {
+            assert_eq!(pinned_cpus(&report, name), [&[0][..]]);
+            assert_eq!(cpus.load(Relaxed), 1);
+        } else {
+            assert_eq!(pinned_cpus(&report, name), [&[][..]]);
+        }
+    }
+
+    // Each thread gets a separate CPU, up to the number of CPUs available.
+    for (pinned, thread_count) in
+        pinned_cpus(&report, "physical_cores").into_iter().zip([1, 2])
+    {
+        assert_eq!(pinned.len(), thread_count.min(allowed.len()));
+        assert!(pinned.iter().all(|cpu| allowed.contains(cpu)));
+
+        let mut unique = pinned.to_vec();
+        unique.sort_unstable();
+        unique.dedup();
+        assert_eq!(unique.len(), pinned.len());
+    }
+
+    // Only the current thread is pinned when benchmarking locally.
+    assert_eq!(pinned_cpus(&report, "local")[0].len(), 1.min(allowed.len()));
+
+    assert_eq!(pinned_cpus(&report, "unpinned"), [&[][..]]);
+
+    // `Divan::pin_cpus` overrides `pin` options.
+    let report =
+        quick_divan(4).pin_cpus(CpuPin::Cpus(vec![].into())).run_benches();
+
+    for bench in report.benches() {
+        assert!(bench.pinned_cpus().is_empty());
+    }
+}
@@ -0,0 +1,221 @@
+// Tests that `Divan::run_benches` returns results for every benchmark.
+
+// Miri cannot discover benchmarks.
+#![cfg(not(miri))]
+
+use std::sync::{Arc, Mutex};
+
+use divan::{
+    counter::ItemsCount,
+    report::{
+        BenchInfo, BenchStats, GroupInfo, OutputFormat, ReportNode, Reporter,
+    },
+    Divan,
+};
+
+use common::divan;
+
+mod common;
+
+#[divan::bench_group(sample_count = 3, sample_size = 2)]
+mod group {
+    #[divan::bench(items_count = 4u32)]
+    fn counted() -> u32 {
+        divan::black_box(4)
+    }
+
+    #[divan::bench(args = [1, 2])]
+    fn with_args(n: u32) -> u32 {
+        divan::black_box(n)
+    }
+
+    #[divan::bench(args = [1, 2, 4, 8])]
+    fn linear(n: usize) -> usize {
+        (0..n).map(divan::black_box).sum()
+    }
+
+    #[divan::bench(threads = [1, 2])]
+    fn threaded() {}
+
+    #[divan::bench]
+    #[ignore]
+    fn ignored() {}
+}
+
+#[test]
+fn report() {
+    let report = divan().run_benches();
+
+    assert!(report.timer().is_some());
+
+    let [ReportNode::Group(root)] = report.nodes() else {
+        panic!("expected single root group");
+    };
+    assert_eq!(root.path(), "report");
+
+    let mut paths: Vec<&str> = report.benches().map(|b| b.path()).collect();
+    paths.sort_unstable();
+    assert_eq!(
+        paths,
+        [
+            "report::group::counted",
+            "report::group::ignored",
+            "report::group::linear::1",
+            "report::group::linear::2",
+            "report::group::linear::4",
+            "report::group::linear::8",
+            "report::group::threaded::t=1",
+            "report::group::threaded::t=2",
+            "report::group::with_args::1",
+            "report::group::with_args::2",
+        ]
+    );
+
+    let [.., ReportNode::Group(group)] = root.children() else {
+        panic!("expected group");
+    };
+    for child in group.children() {
+        let ReportNode::Group(child) = child else { continue };
+
+        // Complexity requires at least 3 numeric arguments.
+        let time_complexity = child.time_complexity();
+        match child.name() {
+            "linear" => assert!(time_complexity.is_some()),
+            _ => assert!(time_complexity.is_none()),
+        }
+        assert!(child.alloc_complexity().is_none());
+    }
+
+    for bench in report.benches() {
+        if bench.name() == "ignored" {
+            assert!(bench.is_ignored());
+            assert!(bench.stats().is_none());
+            continue;
+        }
+
+        let stats = bench.stats().unwrap();
+        assert_eq!(bench.options().sample_count(), Some(3));
+
+        // Sample count becomes a multiple of thread count.
+        let thread_count = bench.thread_count() as u32;
+        assert_eq!(stats.sample_count(), 3u32.next_multiple_of(thread_count));
+        assert_eq!(stats.iter_count(), 2 * stats.sample_count() as u64);
+
+        let time = stats.time_nanos();
+        assert!(time.fastest <= time.slowest);
+
+        let counts = stats.counter::<ItemsCount>();
+        if bench.name() == "counted" {
+            assert_eq!(counts.unwrap().median, 4);
+        } else {
+            assert!(counts.is_none());
+        }
+    }
+}
+
+/// Records events as strings.
+struct EventRecorder(Arc<Mutex<Vec<String>>>);
+
+impl Reporter for EventRecorder {
+    fn start_group(&mut self, group: &GroupInfo) {
+        self.0.lock().unwrap().push(format!("start group {}", group.path()));
+    }
+
+    fn finish_group(&mut self, group: &GroupInfo) {
+        self.0.lock().unwrap().push(format!("finish group {}", group.path()));
+    }
+
+    fn start_bench(&mut self, bench: &BenchInfo) {
+        self.0.lock().unwrap().push(format!("start bench {}", bench.path()));
+    }
+
+    fn finish_bench(&mut self, bench: &BenchInfo, stats: Option<&BenchStats>) {
+        assert!(stats.is_some());
+        self.0.lock().unwrap().push(format!("finish bench {}", bench.path()));
+    }
+
+    fn ignore_bench(&mut self, bench: &BenchInfo) {
+        self.0.lock().unwrap().push(format!("ignore bench {}", bench.path()));
+    }
+}
+
+#[test]
+fn reporter() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+
+    Divan::default()
+        .skip_regex("^divan::")
+        .skip_regex("with_args|threaded|linear")
+        .reporter(EventRecorder(events.clone()))
+        .run_benches();
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            "start group report",
+            "start group report::group",
+            "start bench report::group::counted",
+            "finish bench report::group::counted",
+            "ignore bench report::group::ignored",
+            "finish group report::group",
+            "finish group report",
+        ]
+    );
+}
+
+#[test]
+fn output_format() {
+    let report = Divan::default()
+        .skip_regex("^divan::")
+        .skip_regex("with_args|threaded|linear")
+        .run_benches();
+
+    let mut bmf = Vec::new();
+    report.write(OutputFormat::Bmf, &mut bmf).unwrap();
+    let bmf = String::from_utf8(bmf).unwrap();
+
+    assert!(bmf.starts_with("{\n  \"report::group::counted\": {\n"));
+    assert!(bmf.contains("\"latency\": { \"value\": "));
+    assert!(bmf.contains("\"throughput-items\": { \"value\": "));
+    assert!(!bmf.contains("ignored"));
+
+    let mut gab = Vec::new();
+    report.write(OutputFormat::GithubActionBenchmark, &mut gab).unwrap();
+    let gab = String::from_utf8(gab).unwrap();
+
+    assert!(gab.starts_with(
+        "[\n  { \"name\": \"report::group::counted\", \"unit\": \"ns\", \"value\": "
+    ));
+    assert!(gab.ends_with("}\n]\n"));
+}
+
+#[test]
+fn criterion_dir() {
+    let report = Divan::default()
+        .skip_regex("^divan::")
+        .skip_regex("threaded|ignored|linear")
+        .run_benches();
+
+    let dir = std::env::temp_dir()
+        .join(format!("divan-criterion-{}", std::process::id()));
+    report.write_criterion_dir(&dir).unwrap();
+
+    let read = |path: &str| std::fs::read_to_string(dir.join(path)).unwrap();
+
+    let benchmark = read("report__group/counted/new/benchmark.json");
+    assert!(benchmark.starts_with(
+        r#"{"group_id":"report::group","function_id":"counted","#
+    ));
+    assert!(benchmark.contains(r#""throughput":{"Elements":4}"#));
+
+    let benchmark = read("report__group__with_args/2/base/benchmark.json");
+    assert!(benchmark.contains(r#""full_id":"report::group::with_args/2""#));
+
+    let sample = read("report__group__with_args/2/new/sample.json");
+    assert!(sample.starts_with(r#"{"sampling_mode":"Flat","iters":[2,2,2],"#));
+
+    let estimates = read("report__group__with_args/2/new/estimates.json");
+    assert!(estimates.contains(r#""median":{"confidence_interval":"#));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,75 @@
+// Tests that allocations kept after each sample are reported and limited by
+// `max_retained_bytes`.
+
+// Miri cannot discover benchmarks.
+#![cfg(not(miri))]
+
+use divan::AllocProfiler;
+
+use common::quick_divan;
+
+mod common;
+
+#[global_allocator]
+static ALLOC: AllocProfiler = AllocProfiler::system();
+
+#[divan::bench(max_retained_bytes = 0)]
+fn no_leak() -> Vec<u8> {
+    Vec::with_capacity(64)
+}
+
+#[divan::bench(max_retained_bytes = 0)]
+fn consume_input(bencher: divan::Bencher) {
+    bencher.with_inputs(|| vec![1u8; 32]).bench_values(|v| v.len());
+}
+
+#[divan::bench(max_retained_bytes = 16)]
+fn leak() -> &'static mut [u8; 16] {
+    Box::leak(Box::new([1; 16]))
+}
+
+#[divan::bench(max_retained_bytes = 8)]
+fn leak_too_much() -> &'static mut [u8; 16] {
+    Box::leak(Box::new([1; 16]))
+}
+
+#[test]
+fn retained() {
+    let report =
+        quick_divan(3).skip_exact("retained::leak_too_much").run_benches();
+
+    for bench in report.benches() {
+        let retained = bench.stats().unwrap().retained();
+
+        let expected = match bench.name() {
+            "no_leak" | "consume_input" => (0.0, 0.0),
+            "leak" => (1.0, 16.0),
+            name => panic!("unexpected benchmark {name:?}"),
+        };
+
+        assert_eq!(
+            (retained.count.fastest, retained.size.fastest),
+            expected,
+            "{}",
+            bench.name()
+        );
+        assert_eq!(
+            (retained.count.mean, retained.size.mean),
+            expected,
+            "{}",
+            bench.name()
+        );
+    }
+}
+
+#[test]
+#[should_panic(
+    expected = "benchmark exceeded `max_retained_bytes = 8` per iteration by retaining 16 bytes in 1 allocation over 1 iteration"
+)]
+fn test_exceeds_limit() {
+    quick_divan(3)
+        .skip_exact("retained::no_leak")
+        .skip_exact("retained::consume_input")
+        .skip_exact("retained::leak")
+        .test_benches();
+}
@@ -0,0 +1,34 @@
+// Tests that process memory usage is reported with `Divan::track_rss`.
+
+// Miri cannot discover benchmarks.
+#![cfg(not(miri))]
+#![cfg(target_os = "linux")]
+
+use common::divan;
+
+mod common;
+
+const SIZE: usize = 32 * 1024 * 1024;
+
+#[divan::bench(sample_count = 1, sample_size = 1)]
+fn touch_pages() -> Vec<u8> {
+    vec![1; SIZE]
+}
+
+#[test]
+fn peak_rss() {
+    let report = divan().track_rss(true).run_benches();
+    let stats = report.benches().next().unwrap().stats().unwrap();
+
+    assert!(stats.peak_rss().unwrap() >= SIZE as u64);
+    assert!(stats.rss_delta().is_some());
+}
+
+#[test]
+fn disabled() {
+    let report = divan().run_benches();
+    let stats = report.benches().next().unwrap().stats().unwrap();
+
+    assert_eq!(stats.peak_rss(), None);
+    assert_eq!(stats.rss_delta(), None);
+}
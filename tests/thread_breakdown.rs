@@ -0,0 +1,63 @@
+// Tests per-thread statistics of multi-threaded benchmarks.
+
+// Miri cannot discover benchmarks.
+#![cfg(not(miri))]
+
+use divan::{counter::BytesCount, Bencher};
+
+use common::quick_divan;
+
+mod common;
+
+#[divan::bench(threads = [1, 3])]
+fn sum(bencher: Bencher) {
+    bencher
+        .with_inputs(|| vec![1u8; 64])
+        .input_counter(BytesCount::of_slice)
+        .bench_refs(|v| v.iter().map(|&b| b as u64).sum::<u64>());
+}
+
+#[test]
+fn thread_breakdown() {
+    let report = quick_divan(6).thread_breakdown(true).run_benches();
+
+    let benches: Vec<_> = report.benches().collect();
+    assert_eq!(benches.len(), 2);
+
+    for bench in benches {
+        let stats = bench.stats().unwrap();
+        let threads = stats.threads();
+
+        if bench.thread_count() == 1 {
+            assert!(threads.is_empty());
+            assert_eq!(stats.thread_spread(), None);
+            continue;
+        }
+
+        assert_eq!(threads.len(), 3);
+
+        // Every sample belongs to exactly one thread.
+        let thread_samples: u32 =
+            threads.iter().map(|t| t.sample_count()).sum();
+        assert_eq!(thread_samples, stats.sample_count());
+
+        // Input counts are kept for each thread.
+        for thread in threads {
+            assert_eq!(thread.counter::<BytesCount>().unwrap().mean, 64);
+        }
+
+        let spread = stats.thread_spread().unwrap();
+        assert!(spread.ratio() >= 1.0);
+        assert!(spread.fastest_thread() < 3);
+        assert!(spread.slowest_thread() < 3);
+    }
+}
+
+#[test]
+fn no_thread_breakdown() {
+    let report = quick_divan(6).run_benches();
+
+    for bench in report.benches() {
+        assert!(bench.stats().unwrap().threads().is_empty());
+    }
+}
@@ -0,0 +1,84 @@
+// Tests scaling summaries of benchmarks run over multiple thread counts.
+
+// Miri cannot discover benchmarks.
+#![cfg(not(miri))]
+
+use divan::{
+    counter::{BytesCount, ItemsCount},
+    report::{GroupReport, Report, ReportNode},
+    Bencher, ThreadRole,
+};
+
+use common::quick_divan;
+
+mod common;
+
+#[divan::bench(threads = [1, 2, 4])]
+fn sum(bencher: Bencher) {
+    bencher
+        .counter(BytesCount::new(64usize))
+        .with_inputs(|| vec![1u8; 64])
+        .bench_refs(|v| v.iter().map(|&b| b as u64).sum::<u64>());
+}
+
+#[divan::bench(threads = [2, 4])]
+fn without_single() {}
+
+#[divan::bench(threads = [1, 2])]
+fn local(bencher: Bencher) {
+    bencher.bench_local(|| {});
+}
+
+#[divan::bench(threads = [1, 2])]
+fn roles(bencher: Bencher) {
+    bencher.bench_roles(
+        &[ThreadRole::new("a", 1), ThreadRole::rest("b")],
+        |role| role,
+    );
+}
+
+fn find_group<'a>(report: &'a Report, name: &str) -> &'a GroupReport {
+    let [ReportNode::Group(root)] = report.nodes() else {
+        panic!("expected root group");
+    };
+
+    root.children()
+        .iter()
+        .find_map(|node| match node {
+            ReportNode::Group(group) if group.name() == name => Some(group),
+            _ => None,
+        })
+        .unwrap()
+}
+
+#[test]
+fn thread_scaling() {
+    let report = quick_divan(4).run_benches();
+
+    let scaling = find_group(&report, "sum").thread_scaling();
+    let thread_counts: Vec<usize> =
+        scaling.iter().map(|s| s.thread_count()).collect();
+    assert_eq!(thread_counts, [1, 2, 4]);
+
+    // A single thread is the baseline.
+    assert_eq!(scaling[0].speedup(), Some(1.0));
+    assert_eq!(scaling[0].efficiency(), Some(1.0));
+
+    for s in scaling {
+        if let Some(speedup) = s.speedup() {
+            assert!(speedup > 0.0);
+            assert_eq!(s.efficiency(), Some(speedup / s.thread_count() as f64));
+        }
+
+        // Aggregate throughput is only given for set counters.
+        assert!(s.throughput::<BytesCount>().unwrap() > 0.0);
+        assert_eq!(s.throughput::<ItemsCount>(), None);
+    }
+
+    // Scaling is relative to 1 thread, so it requires a run on 1 thread.
+    assert!(find_group(&report, "without_single").thread_scaling().is_empty());
+
+    // Runs that do not run the same function on each thread are not compared.
+    assert!(find_group(&report, "local").thread_scaling().is_empty());
+    assert!(find_group(&report, "roles").thread_scaling().is_empty());
+}